@@ -0,0 +1,12 @@
+-- Your SQL goes here
+CREATE TABLE sources (
+    citekey TEXT NOT NULL PRIMARY KEY,
+    zettel_id TEXT,
+    title TEXT NOT NULL,
+    authors TEXT NOT NULL DEFAULT '',
+    year INTEGER,
+    publisher TEXT,
+    url TEXT,
+    doi TEXT,
+    FOREIGN KEY(zettel_id) REFERENCES zettels(id)
+);
\ No newline at end of file
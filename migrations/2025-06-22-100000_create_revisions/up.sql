@@ -0,0 +1,11 @@
+-- Your SQL goes here
+CREATE TABLE revisions (
+    id INTEGER NOT NULL PRIMARY KEY AUTOINCREMENT,
+    zettel_id TEXT NOT NULL,
+    revision INTEGER NOT NULL,
+    front_matter TEXT NOT NULL,
+    body TEXT NOT NULL,
+    created_at TIMESTAMP NOT NULL,
+    UNIQUE(zettel_id, revision),
+    FOREIGN KEY(zettel_id) REFERENCES zettels(id)
+);
\ No newline at end of file
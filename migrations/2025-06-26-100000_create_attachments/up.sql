@@ -0,0 +1,11 @@
+-- Your SQL goes here
+CREATE TABLE attachments (
+    id INTEGER NOT NULL PRIMARY KEY AUTOINCREMENT,
+    zettel_id TEXT NOT NULL,
+    file_name TEXT NOT NULL,
+    sha256 TEXT NOT NULL,
+    size BIGINT NOT NULL,
+    created_at TIMESTAMP NOT NULL,
+    UNIQUE(zettel_id, file_name),
+    FOREIGN KEY(zettel_id) REFERENCES zettels(id)
+);
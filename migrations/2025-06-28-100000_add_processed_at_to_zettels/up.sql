@@ -0,0 +1,2 @@
+-- Your SQL goes here
+ALTER TABLE zettels ADD COLUMN processed_at TIMESTAMP;
@@ -0,0 +1,2 @@
+-- Your SQL goes here
+ALTER TABLE attachments ADD COLUMN keep BOOLEAN NOT NULL DEFAULT 0;
@@ -1,11 +1,19 @@
 use crate::{
-    AppConfig, handler::zettel::zettel_new_handler, init_handler, zettel_archive_handler,
-    zettel_edit_handler, zettel_find_handler, zettel_list_handler, zettel_remove_handler,
-    zettel_view_handler,
+    AppConfig, LinkFormat, LinkOptions, NewNoteOptions, SourceMeta, ZettariumError, attach_handler,
+    attachments_gc_handler, attachments_handler, compile_handler, daily_handler, diff_handler,
+    export_bundle_handler, export_html_handler, export_pandoc_handler,
+    handler::zettel::zettel_new_handler, history_handler, import_bib_handler, import_handler,
+    init_handler, log_handler, parse_age, process_handler, promote_handler, reindex_handler,
+    restore_handler, revert_handler, source_list_handler, source_show_handler, trash_empty_handler,
+    trash_list_handler, trash_restore_handler, tui_handler, weekly_handler, zettel_archive_handler,
+    zettel_edit_handler, zettel_find_handler, zettel_list_handler, zettel_path_handler,
+    zettel_remove_handler, zettel_view_handler,
 };
 use anyhow::Result;
-use clap::{Parser, Subcommand};
+use chrono::{Duration, NaiveDate};
+use clap::{Args, Parser, Subcommand};
 use diesel::SqliteConnection;
+use std::io;
 
 #[derive(Parser)]
 pub struct Cli {
@@ -26,6 +34,48 @@ pub enum Commands {
         type_: String,
         #[arg(long, value_delimiter = ',')]
         tags: Option<Vec<String>>,
+        #[arg(long, help = "Template name in the templates directory")]
+        template: Option<String>,
+        #[arg(
+            long,
+            conflicts_with = "stdin",
+            help = "Body text ({{body}} in the template)"
+        )]
+        body: Option<String>,
+        #[arg(long, help = "Read the body from stdin (implies --no-edit)")]
+        stdin: bool,
+        #[arg(long, help = "Save without opening the editor")]
+        no_edit: bool,
+        #[command(flatten)]
+        source: SourceArgs,
+    },
+    #[command(name = "daily")]
+    #[command(about = "Open or create the daily note (ID <date>T000000).")]
+    Daily {
+        #[arg(long, value_name = "YYYY-MM-DD", help = "Defaults to today")]
+        date: Option<NaiveDate>,
+    },
+    #[command(name = "process")]
+    #[command(about = "Work through unprocessed fleeting notes, oldest first.")]
+    Process,
+    #[command(name = "promote")]
+    #[command(about = "Change the type of a note after checking the rules for that type.")]
+    Promote {
+        id: String,
+        #[arg(help = "New note type (e.g. permanent, index)")]
+        type_: String,
+        #[arg(short, long, help = "Change the type even if the rules are not met")]
+        force: bool,
+    },
+    #[command(name = "weekly")]
+    #[command(about = "Print a roll-up of the notes created in a week (Monday to Sunday).")]
+    Weekly {
+        #[arg(
+            long,
+            value_name = "YYYY-MM-DD",
+            help = "Any day of the week (defaults to today)"
+        )]
+        date: Option<NaiveDate>,
     },
     #[command(name = "list", alias = "ls")]
     #[command(about = "Alias: ls \nList Zettelkasten notes.")]
@@ -56,23 +106,146 @@ pub enum Commands {
     #[command(about = "Alias: arc \nArchive Zettelkasten note.")]
     Archive { id: String },
     #[command(name = "remove", alias = "rm")]
-    #[command(about = "Alias: rm \nDelete Zettelkasten note.")]
+    #[command(about = "Alias: rm \nMove Zettelkasten note to the trash.")]
     Remove {
         id: String,
         #[arg(short, long, action = clap::ArgAction::SetTrue)]
         force: bool,
     },
+    #[command(name = "trash")]
+    #[command(about = "List, restore or permanently delete removed notes.")]
+    Trash {
+        #[command(subcommand)]
+        command: TrashCommands,
+    },
+    #[command(name = "attach")]
+    #[command(about = "Copy a file into the note's assets folder and link it from the note.")]
+    Attach {
+        id: String,
+        file: String,
+        #[arg(long, help = "Do not insert a link into the note")]
+        no_link: bool,
+    },
+    #[command(name = "attachments")]
+    #[command(about = "List a note's attachments, or remove unreferenced ones with --gc.")]
+    Attachments {
+        #[arg(required_unless_present = "gc")]
+        id: Option<String>,
+        #[arg(
+            long,
+            conflicts_with = "id",
+            help = "Remove attachments no note links to"
+        )]
+        gc: bool,
+        #[arg(long, requires = "gc")]
+        dry_run: bool,
+    },
     #[command(name = "view", alias = "v")]
     #[command(about = "Alias: v \nView Zettelkasten note in detail.")]
     View { id: String },
     #[command(name = "find", alias = "f")]
     #[command(about = "Alias: f \nFind interactively Zettelkasten note by keyword.")]
     Find {
-        keyword: Option<String>,
+        keywords: Vec<String>,
         #[arg(long, help = "Match against title only", action = clap::ArgAction::SetTrue)]
         title_only: bool,
-        #[arg(long, action = clap::ArgAction::SetTrue)]
+        #[arg(
+            long,
+            help = "Copy links to the selected notes (Tab selects several in fzf)"
+        )]
         link: bool,
+        #[arg(
+            long,
+            requires = "link",
+            help = "Link format: markdown, wikilink, org or id (defaults to [links] format)"
+        )]
+        format: Option<String>,
+        #[arg(
+            long,
+            requires = "link",
+            help = "Print links to stdout instead of the clipboard"
+        )]
+        print: bool,
+        #[arg(
+            long,
+            conflicts_with = "top",
+            help = "Print the best match without fzf"
+        )]
+        first: bool,
+        #[arg(long, value_name = "N", help = "Print the N best matches without fzf")]
+        top: Option<usize>,
+    },
+    #[command(name = "path")]
+    #[command(about = "Print the file path of a note (active or archived).")]
+    Path { id: String },
+    #[command(name = "tui")]
+    #[command(about = "Browse notes in a built-in terminal UI (no fzf or bat needed).")]
+    Tui,
+    #[command(name = "source", alias = "src")]
+    #[command(about = "Alias: src \nShow bibliographic sources of literature notes.")]
+    Source {
+        #[command(subcommand)]
+        command: SourceCommands,
+    },
+    #[command(name = "history")]
+    #[command(about = "List the saved revisions of a note.")]
+    History { id: String },
+    #[command(name = "diff")]
+    #[command(about = "Show a unified diff between two revisions (defaults to the last two).")]
+    Diff {
+        id: String,
+        rev_a: Option<i32>,
+        rev_b: Option<i32>,
+    },
+    #[command(name = "revert")]
+    #[command(about = "Restore a note to a saved revision.")]
+    Revert { id: String, rev: i32 },
+    #[command(name = "log")]
+    #[command(about = "Show the git commits that touched a note.")]
+    Log { id: String },
+    #[command(name = "compile")]
+    #[command(about = "Compile a Structure note and its linked notes into one document.")]
+    Compile {
+        id: String,
+        #[arg(short, long, help = "Output file (defaults to stdout)")]
+        output: Option<String>,
+    },
+    #[command(name = "export")]
+    #[command(about = "Export notes to other formats.")]
+    Export {
+        #[command(subcommand)]
+        command: ExportCommands,
+    },
+    #[command(name = "restore")]
+    #[command(about = "Restore a bundle created by `z export bundle`.")]
+    Restore {
+        file: String,
+        #[arg(long, help = "Overwrite the existing database and notes", action = clap::ArgAction::SetTrue)]
+        force: bool,
+    },
+    #[command(name = "import")]
+    #[command(about = "Import an existing Markdown folder (Obsidian/Logseq/plain).")]
+    Import {
+        dir: String,
+        #[arg(long, default_value = "plain", help = "obsidian | logseq | plain")]
+        format: String,
+        #[arg(long, action = clap::ArgAction::SetTrue)]
+        dry_run: bool,
+    },
+    #[command(name = "import-bib")]
+    #[command(about = "Create or update literature notes from a BibTeX / CSL-JSON file.")]
+    ImportBib {
+        file: String,
+        #[arg(long, help = "bibtex | csl-json (defaults to the file extension)")]
+        format: Option<String>,
+        #[arg(long, action = clap::ArgAction::SetTrue)]
+        dry_run: bool,
+    },
+    #[command(name = "reindex")]
+    #[command(about = "Rebuild the database from the Markdown front matter.")]
+    Reindex {
+        #[arg(long, help = "Only report differences", action = clap::ArgAction::SetTrue)]
+        check: bool,
     },
     // #[command(name = "backlink", alias = "bln")]
     // #[command(about = "Alias: f \nList backlinks.")]
@@ -83,15 +256,167 @@ pub enum Commands {
     // },
 }
 
+#[derive(Subcommand)]
+pub enum SourceCommands {
+    #[command(name = "list", alias = "ls")]
+    #[command(about = "Alias: ls \nList sources.")]
+    List,
+    #[command(name = "show")]
+    #[command(about = "Show a source in detail.")]
+    Show { citekey: String },
+}
+
+#[derive(Subcommand)]
+pub enum TrashCommands {
+    #[command(name = "list", alias = "ls")]
+    #[command(about = "Alias: ls \nList notes in the trash.")]
+    List,
+    #[command(name = "restore")]
+    #[command(about = "Move a note back out of the trash.")]
+    Restore { id: String },
+    #[command(name = "empty")]
+    #[command(about = "Permanently delete notes in the trash.")]
+    Empty {
+        #[arg(
+            long,
+            value_parser = parse_age,
+            help = "Only notes removed longer ago than this (e.g. 30d, 2w, 12h)"
+        )]
+        older_than: Option<Duration>,
+        #[arg(short, long, action = clap::ArgAction::SetTrue)]
+        force: bool,
+    },
+}
+
+#[derive(Subcommand)]
+pub enum ExportCommands {
+    #[command(name = "pandoc")]
+    #[command(about = "Export a Structure note as Pandoc Markdown with its bibliography.")]
+    Pandoc {
+        id: String,
+        #[arg(short, long)]
+        output: String,
+        #[arg(long, default_value = "bibtex", help = "bibtex | csl-json")]
+        bibliography: String,
+    },
+    #[command(name = "html")]
+    #[command(about = "Export non-archived notes as a static HTML site.")]
+    Html {
+        outdir: String,
+        #[arg(
+            long,
+            value_delimiter = ',',
+            help = "Only export notes with these tags"
+        )]
+        tags: Option<Vec<String>>,
+    },
+    #[command(name = "bundle")]
+    #[command(about = "Back up the database, notes and archive into a .tar.zst bundle.")]
+    Bundle { output: String },
+}
+
+#[derive(Args, Default)]
+pub struct SourceArgs {
+    #[arg(long, help = "Citation key of the source (literature notes only)")]
+    pub citekey: Option<String>,
+    #[arg(long = "author", help = "Author of the source (repeatable)")]
+    pub authors: Vec<String>,
+    #[arg(long)]
+    pub year: Option<i32>,
+    #[arg(long, help = "Title of the work (defaults to the note title)")]
+    pub source_title: Option<String>,
+    #[arg(long)]
+    pub publisher: Option<String>,
+    #[arg(long)]
+    pub url: Option<String>,
+    #[arg(long)]
+    pub doi: Option<String>,
+}
+
+impl SourceArgs {
+    pub fn into_meta(self) -> Result<Option<SourceMeta>, ZettariumError> {
+        let Some(citekey) = self.citekey else {
+            let has_metadata = !self.authors.is_empty()
+                || self.year.is_some()
+                || self.source_title.is_some()
+                || self.publisher.is_some()
+                || self.url.is_some()
+                || self.doi.is_some();
+            if has_metadata {
+                return Err(ZettariumError::InvalidSource(
+                    "--citekey is required to store source metadata".into(),
+                ));
+            }
+            return Ok(None);
+        };
+
+        Ok(Some(SourceMeta {
+            citekey,
+            title: self.source_title,
+            authors: self.authors,
+            year: self.year,
+            publisher: self.publisher,
+            url: self.url,
+            doi: self.doi,
+        }))
+    }
+}
+
+fn require_connection(
+    conn: Option<&mut SqliteConnection>,
+) -> Result<&mut SqliteConnection, ZettariumError> {
+    conn.ok_or_else(|| ZettariumError::ConfigInvalid("DB connection not available".into()))
+}
+
 pub fn dispatch(cli: Cli, conn: Option<&mut SqliteConnection>, config: &AppConfig) -> Result<()> {
     match cli.command {
         Commands::Init => {
             init_handler(config)?;
             Ok(())
         }
-        Commands::New { title, type_, tags } => {
-            let conn = conn.expect("DB connection not available");
-            zettel_new_handler(conn, &title, &type_, &tags, config)?;
+        Commands::New {
+            title,
+            type_,
+            tags,
+            template,
+            body,
+            stdin,
+            no_edit,
+            source,
+        } => {
+            let conn = require_connection(conn)?;
+            let source = source.into_meta()?;
+            let body = if stdin {
+                Some(io::read_to_string(io::stdin())?)
+            } else {
+                body
+            };
+            let options = NewNoteOptions {
+                template: template.as_deref(),
+                body,
+                edit: !no_edit && !stdin,
+            };
+            zettel_new_handler(conn, &title, &type_, &tags, source, options, config)?;
+            Ok(())
+        }
+        Commands::Daily { date } => {
+            let conn = require_connection(conn)?;
+            daily_handler(conn, date, config)?;
+            Ok(())
+        }
+        Commands::Process => {
+            let conn = require_connection(conn)?;
+            process_handler(conn, config)?;
+            Ok(())
+        }
+        Commands::Promote { id, type_, force } => {
+            let conn = require_connection(conn)?;
+            promote_handler(conn, &id, &type_, force, config)?;
+            Ok(())
+        }
+        Commands::Weekly { date } => {
+            let conn = require_connection(conn)?;
+            weekly_handler(conn, date)?;
             Ok(())
         }
         Commands::List {
@@ -101,8 +426,16 @@ pub fn dispatch(cli: Cli, conn: Option<&mut SqliteConnection>, config: &AppConfi
             all,
             archived,
         } => {
-            let conn = conn.expect("DB connection not available");
-            zettel_list_handler(conn, id.as_deref(), type_.as_deref(), &tags, all, archived)?;
+            let conn = require_connection(conn)?;
+            zettel_list_handler(
+                conn,
+                id.as_deref(),
+                type_.as_deref(),
+                &tags,
+                all,
+                archived,
+                config,
+            )?;
             Ok(())
         }
         Commands::Edit {
@@ -111,32 +444,154 @@ pub fn dispatch(cli: Cli, conn: Option<&mut SqliteConnection>, config: &AppConfi
             type_,
             tags,
         } => {
-            let conn = conn.expect("DB connection not available");
+            let conn = require_connection(conn)?;
             zettel_edit_handler(conn, &id, title.as_deref(), type_.as_deref(), &tags, config)?;
             Ok(())
         }
         Commands::Archive { id } => {
-            let conn = conn.expect("DB connection not available");
+            let conn = require_connection(conn)?;
             zettel_archive_handler(conn, &id, config)?;
             Ok(())
         }
         Commands::Remove { id, force } => {
-            let conn = conn.expect("DB connection not available");
-            let _result = zettel_remove_handler(conn, &id, force, config)?;
+            let conn = require_connection(conn)?;
+            zettel_remove_handler(conn, &id, force, config)?;
+            Ok(())
+        }
+        Commands::Trash { command } => {
+            let conn = require_connection(conn)?;
+            match command {
+                TrashCommands::List => trash_list_handler(conn)?,
+                TrashCommands::Restore { id } => trash_restore_handler(conn, &id, config)?,
+                TrashCommands::Empty { older_than, force } => {
+                    trash_empty_handler(conn, older_than, force, config)?
+                }
+            }
+            Ok(())
+        }
+        Commands::Attach { id, file, no_link } => {
+            let conn = require_connection(conn)?;
+            attach_handler(conn, &id, &file, no_link, config)?;
+            Ok(())
+        }
+        Commands::Attachments { id, gc, dry_run } => {
+            let conn = require_connection(conn)?;
+            match id {
+                Some(id) if !gc => attachments_handler(conn, &id, config)?,
+                _ => attachments_gc_handler(conn, dry_run, config)?,
+            }
             Ok(())
         }
         Commands::View { id } => {
-            let conn = conn.expect("DB connection not available");
+            let conn = require_connection(conn)?;
             zettel_view_handler(conn, &id, config)?;
             Ok(())
         }
         Commands::Find {
-            keyword,
+            keywords,
             title_only,
             link,
+            format,
+            print,
+            first,
+            top,
         } => {
-            let conn = conn.expect("DB connection not available");
-            zettel_find_handler(conn, keyword.as_deref(), title_only, link, config)?;
+            let conn = require_connection(conn)?;
+            let link = if link {
+                let format = match format {
+                    Some(format) => format.parse::<LinkFormat>()?,
+                    None => config.links.format,
+                };
+                Some(LinkOptions { format, print })
+            } else {
+                None
+            };
+            zettel_find_handler(conn, &keywords, title_only, link, first, top, config)?;
+            Ok(())
+        }
+        Commands::Path { id } => {
+            let conn = require_connection(conn)?;
+            zettel_path_handler(conn, &id, config)?;
+            Ok(())
+        }
+        Commands::Tui => {
+            let conn = require_connection(conn)?;
+            tui_handler(conn, config)?;
+            Ok(())
+        }
+        Commands::Source { command } => {
+            let conn = require_connection(conn)?;
+            match command {
+                SourceCommands::List => source_list_handler(conn)?,
+                SourceCommands::Show { citekey } => source_show_handler(conn, &citekey)?,
+            }
+            Ok(())
+        }
+        Commands::History { id } => {
+            let conn = require_connection(conn)?;
+            history_handler(conn, &id)?;
+            Ok(())
+        }
+        Commands::Diff { id, rev_a, rev_b } => {
+            let conn = require_connection(conn)?;
+            diff_handler(conn, &id, rev_a, rev_b)?;
+            Ok(())
+        }
+        Commands::Revert { id, rev } => {
+            let conn = require_connection(conn)?;
+            revert_handler(conn, &id, rev, config)?;
+            Ok(())
+        }
+        Commands::Log { id } => {
+            let conn = require_connection(conn)?;
+            log_handler(conn, &id, config)?;
+            Ok(())
+        }
+        Commands::Compile { id, output } => {
+            let conn = require_connection(conn)?;
+            compile_handler(conn, &id, output.as_deref(), config)?;
+            Ok(())
+        }
+        Commands::Export { command } => {
+            let conn = require_connection(conn)?;
+            match command {
+                ExportCommands::Pandoc {
+                    id,
+                    output,
+                    bibliography,
+                } => export_pandoc_handler(conn, &id, &output, &bibliography, config)?,
+                ExportCommands::Bundle { output } => export_bundle_handler(conn, &output, config)?,
+                ExportCommands::Html { outdir, tags } => {
+                    export_html_handler(conn, &outdir, &tags, config)?
+                }
+            }
+            Ok(())
+        }
+        Commands::Restore { file, force } => {
+            restore_handler(&file, force, config)?;
+            Ok(())
+        }
+        Commands::Import {
+            dir,
+            format,
+            dry_run,
+        } => {
+            let conn = require_connection(conn)?;
+            import_handler(conn, &dir, &format, dry_run, config)?;
+            Ok(())
+        }
+        Commands::ImportBib {
+            file,
+            format,
+            dry_run,
+        } => {
+            let conn = require_connection(conn)?;
+            import_bib_handler(conn, &file, format.as_deref(), dry_run, config)?;
+            Ok(())
+        }
+        Commands::Reindex { check } => {
+            let conn = require_connection(conn)?;
+            reindex_handler(conn, check, config)?;
             Ok(())
         }
     }
@@ -1,11 +1,131 @@
-use config::{Config, ConfigError, File};
+use crate::{LinkFormat, NoteType, ZettariumError};
+use config::{Config, File};
 use etcetera::{BaseStrategy, choose_base_strategy};
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 
 #[derive(Debug, Deserialize)]
 pub struct AppConfig {
     pub paths: PathsConfig,
     pub editor: EditorConfig,
+    #[serde(default)]
+    pub templates: TemplatesConfig,
+    #[serde(default)]
+    pub git: GitConfig,
+    #[serde(default)]
+    pub finder: FinderConfig,
+    #[serde(default)]
+    pub links: LinksConfig,
+    #[serde(default)]
+    pub journal: JournalConfig,
+    // ノートの種類ごとの設定（キーは permanent などの種類名）
+    #[serde(default)]
+    pub types: HashMap<String, TypeConfig>,
+}
+
+impl AppConfig {
+    pub fn type_config(&self, type_: &NoteType) -> Option<&TypeConfig> {
+        self.types
+            .iter()
+            .find(|(name, _)| name.to_lowercase() == type_.name())
+            .map(|(_, config)| config)
+    }
+
+    // `--type` などの名前・略称を種類に直す（組み込みの名前と略称を優先する）
+    pub fn parse_type(&self, s: &str) -> Result<NoteType, ZettariumError> {
+        if let Ok(type_) = s.parse() {
+            return Ok(type_);
+        }
+        let lower = s.to_lowercase();
+        let name = self.types.iter().find(|(name, config)| {
+            name.to_lowercase() == lower
+                || config.alias.as_deref().map(str::to_lowercase).as_deref() == Some(lower.as_str())
+        });
+        match name {
+            // [types.permanent] で組み込みの種類に略称を足した場合も含む
+            Some((name, _)) => NoteType::from_name(name),
+            None => Err(ZettariumError::InvalidNoteType(s.into())),
+        }
+    }
+
+    // 名前や略称が重なると `--type` の解釈が決まらないので、読み込み時に弾く
+    pub fn validate_types(&self) -> Result<(), ZettariumError> {
+        let mut types: Vec<(String, &TypeConfig)> = self
+            .types
+            .iter()
+            .map(|(name, config)| (name.to_lowercase(), config))
+            .collect();
+        types.sort_by(|a, b| a.0.cmp(&b.0));
+
+        // 名前・略称 → [types.<name>]
+        let mut used: HashMap<String, String> = HashMap::new();
+        for (i, (name, config)) in types.iter().enumerate() {
+            if i > 0 && types[i - 1].0 == *name {
+                return Err(ZettariumError::ConfigInvalid(format!(
+                    "[types.{}] is defined more than once",
+                    name
+                )));
+            }
+            let builtin = NoteType::ALL.into_iter().find(|t| t.name() == name);
+            let alias = config.alias.as_ref().map(|a| a.to_lowercase());
+            for key in std::iter::once(name.clone()).chain(alias) {
+                // 組み込みの名前・略称はその種類の設定でだけ使える
+                if let Ok(type_) = key.parse::<NoteType>() {
+                    if Some(&type_) != builtin.as_ref() {
+                        return Err(ZettariumError::ConfigInvalid(format!(
+                            "[types.{}]: \"{}\" is already used by the built-in type {}",
+                            name, key, type_
+                        )));
+                    }
+                    continue;
+                }
+                if let Some(other) = used.insert(key.clone(), name.clone())
+                    && other != *name
+                {
+                    return Err(ZettariumError::ConfigInvalid(format!(
+                        "[types.{}]: \"{}\" is already used by [types.{}]",
+                        name, key, other
+                    )));
+                }
+            }
+        }
+        Ok(())
+    }
+
+    // 組み込みの種類のあとに設定で追加した種類を名前順に並べる
+    pub fn note_types(&self) -> Vec<NoteType> {
+        let mut custom: Vec<NoteType> = self
+            .types
+            .keys()
+            .filter_map(|name| NoteType::from_name(name).ok())
+            .filter(|type_| matches!(type_, NoteType::Custom(_)))
+            .collect();
+        custom.sort_by(|a, b| a.name().cmp(b.name()));
+        NoteType::ALL.into_iter().chain(custom).collect()
+    }
+
+    pub fn type_emoji(&self, type_: &NoteType) -> String {
+        if let Some(emoji) = self.type_config(type_).and_then(|c| c.emoji.clone()) {
+            return emoji;
+        }
+        match type_ {
+            NoteType::Fleeting => "📝",
+            NoteType::Permanent => "🧠",
+            NoteType::Literature => "📚",
+            NoteType::Structure => "🏗",
+            NoteType::Index => "🗂",
+            NoteType::Custom(_) => "📄",
+        }
+        .into()
+    }
+
+    // 設定がなければ組み込みのルールを使う
+    pub fn type_rules(&self, type_: &NoteType) -> TypeRules {
+        match self.type_config(type_).and_then(|c| c.rules.clone()) {
+            Some(rules) => rules,
+            None => TypeRules::builtin(type_),
+        }
+    }
 }
 
 #[derive(Serialize, Deserialize, Debug)]
@@ -14,6 +134,26 @@ pub struct PathsConfig {
     pub db_path: String,
     pub zettel_dir: String,
     pub archive_dir: String,
+    // 省略時は <zettel_dir>/.trash
+    #[serde(default)]
+    pub trash_dir: Option<String>,
+}
+
+impl PathsConfig {
+    pub fn trash_dir(&self) -> String {
+        self.trash_dir
+            .clone()
+            .unwrap_or_else(|| format!("{}/.trash", self.zettel_dir))
+    }
+
+    // アーカイブ済みのノートは archive_dir にある
+    pub fn note_dir(&self, archived: bool) -> &str {
+        if archived {
+            &self.archive_dir
+        } else {
+            &self.zettel_dir
+        }
+    }
 }
 
 #[derive(Serialize, Deserialize, Debug)]
@@ -21,8 +161,126 @@ pub struct EditorConfig {
     pub editor: String,
 }
 
-pub fn load_config() -> Result<AppConfig, ConfigError> {
-    let strategy = choose_base_strategy().expect("Unable to find the config directory!");
+#[derive(Serialize, Deserialize, Debug)]
+pub struct TemplatesConfig {
+    // <dir>/<type>.md がNoteTypeごとの既定テンプレート、それ以外は `--template <name>` で選ぶ
+    pub dir: String,
+}
+
+impl Default for TemplatesConfig {
+    fn default() -> Self {
+        let dir = choose_base_strategy()
+            .map(|s| s.config_dir().join("zettarium").join("templates"))
+            .unwrap_or_else(|_| "templates".into());
+        TemplatesConfig {
+            dir: dir.display().to_string(),
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Default)]
+pub struct GitConfig {
+    // zettel_dirがgitリポジトリなら new / edit / archive / remove のたびにコミットする
+    #[serde(default)]
+    pub auto_commit: bool,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(default)]
+pub struct FinderConfig {
+    pub command: String,
+    pub options: Vec<String>,
+    // {path} / {id} / {zettarium} を置き換えて実行する（空文字ならプレビューなし）
+    // 省略時は bat → batcat → `zettarium view` の順に使えるものを選ぶ
+    pub preview: Option<String>,
+}
+
+impl Default for FinderConfig {
+    fn default() -> Self {
+        FinderConfig {
+            command: "fzf".into(),
+            options: [
+                "--ansi",
+                "--preview-window=right:70%",
+                "--prompt",
+                "🔍 Search: ",
+            ]
+            .map(String::from)
+            .to_vec(),
+            preview: None,
+        }
+    }
+}
+
+#[derive(Deserialize, Debug, Default)]
+pub struct LinksConfig {
+    // markdown / wikilink / org / id
+    #[serde(default)]
+    pub format: LinkFormat,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(default)]
+pub struct JournalConfig {
+    // テンプレートディレクトリ内の名前（省略時は daily.md → 組み込み）
+    pub template: Option<String>,
+    pub tags: Vec<String>,
+}
+
+impl Default for JournalConfig {
+    fn default() -> Self {
+        JournalConfig {
+            template: None,
+            tags: vec!["daily".into()],
+        }
+    }
+}
+
+// [types.<name>] 組み込みの種類の設定を変えるか、新しい種類を追加する
+#[derive(Deserialize, Debug, Default)]
+#[serde(default)]
+pub struct TypeConfig {
+    // `--type` で使える略称
+    pub alias: Option<String>,
+    pub emoji: Option<String>,
+    // テンプレートディレクトリ内の名前（省略時は <name>.md）
+    pub template: Option<String>,
+    // 省略時は組み込みのルール（追加した種類にはルールなし）
+    pub rules: Option<TypeRules>,
+}
+
+// `z promote` でその種類にする前に満たしている必要がある条件
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq, Eq)]
+#[serde(default)]
+pub struct TypeRules {
+    pub min_tags: usize,
+    // 本文中の他のノートへのリンクの数
+    pub min_links: usize,
+    // 見出し以外はノートへのリンクだけ
+    pub links_only: bool,
+}
+
+impl TypeRules {
+    pub fn builtin(type_: &NoteType) -> Self {
+        match type_ {
+            NoteType::Permanent => TypeRules {
+                min_tags: 1,
+                min_links: 1,
+                links_only: false,
+            },
+            NoteType::Index => TypeRules {
+                links_only: true,
+                ..TypeRules::default()
+            },
+            _ => TypeRules::default(),
+        }
+    }
+}
+
+pub fn load_config() -> Result<AppConfig, ZettariumError> {
+    let strategy = choose_base_strategy().map_err(|e| {
+        ZettariumError::ConfigInvalid(format!("Unable to find the config directory: {}", e))
+    })?;
     let mut path = strategy.config_dir();
     path.push("zettarium");
     path.push("config.toml");
@@ -31,7 +289,57 @@ pub fn load_config() -> Result<AppConfig, ConfigError> {
         eprintln!("No config file found at: {}", path.display());
     }
 
-    let builder = Config::builder().add_source(File::from(path));
+    let builder = Config::builder().add_source(File::from(path.clone()));
 
-    builder.build()?.try_deserialize()
+    let config = builder
+        .build()
+        .and_then(|c| c.try_deserialize::<AppConfig>())
+        .map_err(|e| ZettariumError::ConfigInvalid(format!("{}: {}", path.display(), e)))?;
+    config.validate_types().map_err(|e| match e {
+        ZettariumError::ConfigInvalid(message) => {
+            ZettariumError::ConfigInvalid(format!("{}: {}", path.display(), message))
+        }
+        e => e,
+    })?;
+    Ok(config)
+}
+
+// Test
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use config::FileFormat;
+
+    fn config_with_types(types: &str) -> AppConfig {
+        let toml = format!(
+            "[paths]\ndb_path = \"z.db\"\nzettel_dir = \"notes\"\narchive_dir = \"archive\"\n\
+             [editor]\neditor = \"vi\"\n{}",
+            types
+        );
+        Config::builder()
+            .add_source(File::from_str(&toml, FileFormat::Toml))
+            .build()
+            .and_then(|c| c.try_deserialize())
+            .unwrap()
+    }
+
+    #[test]
+    fn test_validate_types() {
+        let valid = config_with_types(
+            "[types.project]\nalias = \"pr\"\n[types.permanent]\nalias = \"perm\"\n",
+        );
+        assert!(valid.validate_types().is_ok());
+
+        for types in [
+            "[types.project]\nalias = \"q\"\n[types.question]\nalias = \"Q\"\n",
+            "[types.project]\nalias = \"question\"\n[types.question]\n",
+            "[types.project]\nalias = \"p\"\n",
+            "[types.literature]\nalias = \"perm\"\n[types.project]\nalias = \"perm\"\n",
+            "[types.f]\n",
+            "[types.Project]\n[types.project]\n",
+        ] {
+            let err = config_with_types(types).validate_types().unwrap_err();
+            assert!(matches!(err, ZettariumError::ConfigInvalid(_)), "{}", types);
+        }
+    }
 }
@@ -1,7 +1,111 @@
+use std::path::PathBuf;
 use thiserror::Error;
 
 #[derive(Debug, Error)]
 pub enum ZettariumError {
     #[error("Invalid note type: {0}")]
     InvalidNoteType(String),
+    #[error("Invalid import format: {0}")]
+    InvalidImportFormat(String),
+    #[error("Note not found: {0}")]
+    NoteNotFound(String),
+    #[error("Note {0} is already archived")]
+    AlreadyArchived(String),
+    #[error("Zettel file does not exist: {}", .0.display())]
+    FileMissing(PathBuf),
+    #[error("Invalid config: {0}")]
+    ConfigInvalid(String),
+    #[error("Editor failed: {0}")]
+    EditorFailed(String),
+    #[error("Invalid front matter in {}:{line}: {message}", path.display())]
+    FrontMatterInvalid {
+        path: PathBuf,
+        line: usize,
+        message: String,
+    },
+    #[error("Failed to access {}: {source}", path.display())]
+    Io {
+        path: PathBuf,
+        source: std::io::Error,
+    },
+    #[error("Database error: {0}")]
+    Database(#[from] diesel::result::Error),
+    #[error("Failed to open database {path}: {message}")]
+    DatabaseUnavailable { path: String, message: String },
+    #[error("Failed to migrate database: {0}")]
+    Migration(String),
+    #[error("Template not found: {0}")]
+    TemplateNotFound(String),
+    #[error("Invalid source: {0}")]
+    InvalidSource(String),
+    #[error("Source not found: {0}")]
+    SourceNotFound(String),
+    #[error("Invalid bibliography {}:{line}: {message}", path.display())]
+    BibliographyInvalid {
+        path: PathBuf,
+        line: usize,
+        message: String,
+    },
+    #[error("Invalid bundle: {0}")]
+    BundleInvalid(String),
+    #[error("Refusing to overwrite {}: use --force", .0.display())]
+    RestoreTargetExists(PathBuf),
+    #[error("Revision {revision} of note {id} not found")]
+    RevisionNotFound { id: String, revision: i32 },
+    #[error("Git error: {0}")]
+    Git(String),
+    #[error("Invalid link format: {0}")]
+    InvalidLinkFormat(String),
+    #[error("Note {id} does not meet the rules for {type_}: {}", violations.join("; "))]
+    RuleViolation {
+        id: String,
+        type_: String,
+        violations: Vec<String>,
+    },
+}
+
+impl ZettariumError {
+    // スクリプトから原因を判別できるよう、種類ごとに終了コードを分ける
+    // 1 は想定外のエラー、2 は clap の引数エラーで使われる
+    pub fn exit_code(&self) -> i32 {
+        match self {
+            ZettariumError::InvalidNoteType(_) => 3,
+            ZettariumError::InvalidImportFormat(_) => 4,
+            ZettariumError::NoteNotFound(_) => 5,
+            ZettariumError::AlreadyArchived(_) => 6,
+            ZettariumError::FileMissing(_) => 7,
+            ZettariumError::ConfigInvalid(_) => 8,
+            ZettariumError::EditorFailed(_) => 9,
+            ZettariumError::FrontMatterInvalid { .. } => 10,
+            ZettariumError::Io { .. } => 11,
+            ZettariumError::Database(_) => 12,
+            ZettariumError::DatabaseUnavailable { .. } => 13,
+            ZettariumError::Migration(_) => 14,
+            ZettariumError::TemplateNotFound(_) => 15,
+            ZettariumError::InvalidSource(_) => 16,
+            ZettariumError::SourceNotFound(_) => 17,
+            ZettariumError::BibliographyInvalid { .. } => 18,
+            ZettariumError::BundleInvalid(_) => 19,
+            ZettariumError::RestoreTargetExists(_) => 20,
+            ZettariumError::RevisionNotFound { .. } => 21,
+            ZettariumError::Git(_) => 22,
+            ZettariumError::InvalidLinkFormat(_) => 23,
+            ZettariumError::RuleViolation { .. } => 24,
+        }
+    }
+
+    // ファイルが存在しない場合は FileMissing として扱う
+    pub fn io(path: impl Into<PathBuf>, source: std::io::Error) -> Self {
+        let path = path.into();
+        if source.kind() == std::io::ErrorKind::NotFound {
+            return ZettariumError::FileMissing(path);
+        }
+        ZettariumError::Io { path, source }
+    }
+}
+
+pub fn exit_code_of(err: &anyhow::Error) -> i32 {
+    err.chain()
+        .find_map(|e| e.downcast_ref::<ZettariumError>())
+        .map_or(1, ZettariumError::exit_code)
 }
@@ -0,0 +1,276 @@
+use anyhow::Result;
+use diesel::SqliteConnection;
+use std::{
+    collections::HashSet,
+    fs,
+    path::{Path, PathBuf},
+};
+
+use crate::{
+    AppConfig, Body, FrontMatter, GitAction, Markdown, ZettariumError, auto_commit,
+    create_attachment, delete_attachment, ensure_zettel_exists, find_attachment_by_hash,
+    get_tag_by_zettel_id, hash_file, list_all_attachments, list_attachments, list_zettels,
+    parse_markdown, print_attachments_as_table, update_zettel_timestamp_only, write_to_markdown,
+};
+
+pub fn attach_handler(
+    conn: &mut SqliteConnection,
+    id: &str,
+    file: &str,
+    no_link: bool,
+    config: &AppConfig,
+) -> Result<()> {
+    let zettel = ensure_zettel_exists(conn, id)?;
+    let source = Path::new(file);
+    if !source.is_file() {
+        return Err(ZettariumError::FileMissing(source.to_path_buf()).into());
+    }
+
+    // 添付ファイルはノートと同じ場所の assets/<id>/ に置く
+    let dir = config.paths.note_dir(zettel.archived);
+    let (size, hash) = hash_file(source)?;
+    let attachment = match find_attachment_by_hash(conn, &zettel.id, &hash)? {
+        Some(existing) => {
+            println!("{} is already attached as {}", file, existing.file_name);
+            existing
+        }
+        None => {
+            let assets = assets_dir(dir, &zettel.id);
+            fs::create_dir_all(&assets).map_err(|e| ZettariumError::io(&assets, e))?;
+            let name = unique_file_name(&assets, &sanitize_file_name(source));
+            let path = assets.join(&name);
+            fs::copy(source, &path).map_err(|e| ZettariumError::io(&path, e))?;
+
+            let attachment =
+                create_attachment(conn, &zettel.id, &name, &hash, size as i64, no_link)?;
+            println!("Attached {} to {}", path.display(), zettel.id);
+            attachment
+        }
+    };
+
+    let parsed = parse_markdown(&zettel, dir.into())?;
+    // 既にリンクしてあれば本文はそのまま
+    if !no_link && !parsed.body.contains(&attachment.asset_path()) {
+        let body = format!(
+            "{}\n\n{}\n",
+            parsed.body.trim_start_matches('\n').trim_end(),
+            attachment.markdown_link()
+        );
+        let updated = update_zettel_timestamp_only(conn, &zettel.id)?;
+        let tags = get_tag_by_zettel_id(conn, &zettel.id)?
+            .into_iter()
+            .map(|t| t.tag_name)
+            .collect();
+        let markdown = Markdown {
+            front_matter: FrontMatter {
+                zettel: updated,
+                tags,
+                source: parsed.front_matter.and_then(|f| f.source),
+            },
+            body: Body(body),
+        };
+        write_to_markdown(conn, &markdown, dir.into())?;
+    }
+
+    auto_commit(config, GitAction::Attach, &zettel);
+    Ok(())
+}
+
+pub fn attachments_handler(
+    conn: &mut SqliteConnection,
+    id: &str,
+    config: &AppConfig,
+) -> Result<()> {
+    let zettel = ensure_zettel_exists(conn, id)?;
+    let attachments = list_attachments(conn, &zettel.id)?;
+    let body = parse_markdown(&zettel, config.paths.note_dir(zettel.archived).into())?.body;
+
+    // Display
+    print_attachments_as_table(&attachments, &body)?;
+    Ok(())
+}
+
+// 本文から参照されていない添付ファイルと、どのノートにも記録されていないファイルを消す
+// `--no-link` で添付したものと、ゴミ箱のノートの添付ファイル（`z trash empty` まで）は残す
+pub fn attachments_gc_handler(
+    conn: &mut SqliteConnection,
+    dry_run: bool,
+    config: &AppConfig,
+) -> Result<()> {
+    let mut unreferenced: Vec<PathBuf> = vec![];
+    let mut dropped: HashSet<(String, String)> = HashSet::new();
+
+    for zettel in list_zettels(conn, None, None, &[], true, false)? {
+        let attachments = list_attachments(conn, &zettel.id)?;
+        if attachments.is_empty() {
+            continue;
+        }
+        let dir = config.paths.note_dir(zettel.archived);
+        let body = match parse_markdown(&zettel, dir.into()) {
+            Ok(parsed) => parsed.body,
+            Err(ZettariumError::FileMissing(path)) => {
+                eprintln!(
+                    "Warning: Skipped {}: {} is missing",
+                    zettel.id,
+                    path.display()
+                );
+                continue;
+            }
+            Err(e) => return Err(e.into()),
+        };
+
+        for attachment in attachments {
+            if attachment.keep || body.contains(&attachment.asset_path()) {
+                continue;
+            }
+            unreferenced.push(assets_dir(dir, &zettel.id).join(&attachment.file_name));
+            dropped.insert((attachment.zettel_id.clone(), attachment.file_name.clone()));
+            if !dry_run {
+                delete_attachment(conn, attachment.id)?;
+            }
+        }
+    }
+
+    // DBに記録のないファイル（手で置いたもの、ノートごと消えたものなど）
+    let known: HashSet<(String, String)> = list_all_attachments(conn)?
+        .into_iter()
+        .map(|a| (a.zettel_id, a.file_name))
+        .filter(|key| !dropped.contains(key))
+        .collect();
+    let mut orphans: Vec<PathBuf> = vec![];
+    let trash_dir = config.paths.trash_dir();
+    let mut roots = vec![
+        config.paths.zettel_dir.as_str(),
+        config.paths.archive_dir.as_str(),
+        trash_dir.as_str(),
+    ];
+    roots.dedup();
+    for root in roots {
+        for (note_id, path) in list_asset_files(&Path::new(root).join("assets"))? {
+            let name = path
+                .file_name()
+                .map(|n| n.to_string_lossy().to_string())
+                .unwrap_or_default();
+            if !known.contains(&(note_id, name)) && !unreferenced.contains(&path) {
+                orphans.push(path);
+            }
+        }
+    }
+
+    let mut total = 0;
+    for path in unreferenced.iter().chain(&orphans) {
+        if !path.exists() {
+            continue;
+        }
+        total += 1;
+        if dry_run {
+            println!("Would remove {}", path.display());
+            continue;
+        }
+        fs::remove_file(path).map_err(|e| ZettariumError::io(path, e))?;
+        println!("Removed {}", path.display());
+        // 空になった assets/<id>/ も消す
+        if let Some(dir) = path.parent()
+            && fs::read_dir(dir).is_ok_and(|mut entries| entries.next().is_none())
+        {
+            fs::remove_dir(dir).map_err(|e| ZettariumError::io(dir, e))?;
+        }
+    }
+
+    if dry_run {
+        println!("{} unreferenced attachments would be removed.", total);
+    } else {
+        println!("Removed {} unreferenced attachments.", total);
+    }
+    Ok(())
+}
+
+pub fn assets_dir(dir: &str, id: &str) -> PathBuf {
+    Path::new(dir).join("assets").join(id)
+}
+
+// ノートの移動（archive / trash）に合わせて assets/<id>/ も移す
+pub fn move_assets(from: &str, to: &str, id: &str) -> Result<(), ZettariumError> {
+    let from = assets_dir(from, id);
+    if !from.exists() {
+        return Ok(());
+    }
+    let to = assets_dir(to, id);
+    if let Some(parent) = to.parent() {
+        fs::create_dir_all(parent).map_err(|e| ZettariumError::io(parent, e))?;
+    }
+    fs::rename(&from, &to).map_err(|e| ZettariumError::io(&from, e))
+}
+
+pub fn remove_assets(dir: &str, id: &str) -> Result<(), ZettariumError> {
+    let assets = assets_dir(dir, id);
+    if !assets.exists() {
+        return Ok(());
+    }
+    fs::remove_dir_all(&assets).map_err(|e| ZettariumError::io(&assets, e))
+}
+
+// Markdownのリンクに使えるよう、空白や記号を `-` に置き換える
+fn sanitize_file_name(path: &Path) -> String {
+    let name = path
+        .file_name()
+        .map(|n| n.to_string_lossy().to_string())
+        .unwrap_or_default();
+    let sanitized: String = name
+        .chars()
+        .map(|c| {
+            if c.is_alphanumeric() || matches!(c, '.' | '-' | '_') {
+                c
+            } else {
+                '-'
+            }
+        })
+        .collect();
+    let sanitized = sanitized.trim_matches(['-', '.']).to_string();
+    if sanitized.is_empty() {
+        "attachment".into()
+    } else {
+        sanitized
+    }
+}
+
+// 同じ名前の別ファイルがあれば `name-1.ext` のように番号を付ける
+fn unique_file_name(dir: &Path, name: &str) -> String {
+    if !dir.join(name).exists() {
+        return name.to_string();
+    }
+    let (stem, ext) = match name.rsplit_once('.') {
+        Some((stem, ext)) if !stem.is_empty() => (stem, format!(".{}", ext)),
+        _ => (name, String::new()),
+    };
+    (1..)
+        .map(|n| format!("{}-{}{}", stem, n, ext))
+        .find(|candidate| !dir.join(candidate).exists())
+        .unwrap_or_else(|| name.to_string())
+}
+
+// assets/<id>/<file> を (id, path) で返す
+fn list_asset_files(assets: &Path) -> Result<Vec<(String, PathBuf)>, ZettariumError> {
+    let mut files = vec![];
+    if !assets.is_dir() {
+        return Ok(files);
+    }
+    for entry in fs::read_dir(assets).map_err(|e| ZettariumError::io(assets, e))? {
+        let dir = entry.map_err(|e| ZettariumError::io(assets, e))?.path();
+        if !dir.is_dir() {
+            continue;
+        }
+        let note_id = dir
+            .file_name()
+            .map(|n| n.to_string_lossy().to_string())
+            .unwrap_or_default();
+        for file in fs::read_dir(&dir).map_err(|e| ZettariumError::io(&dir, e))? {
+            let path = file.map_err(|e| ZettariumError::io(&dir, e))?.path();
+            if path.is_file() {
+                files.push((note_id.clone(), path));
+            }
+        }
+    }
+    files.sort();
+    Ok(files)
+}
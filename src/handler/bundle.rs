@@ -0,0 +1,38 @@
+use anyhow::Result;
+use diesel::SqliteConnection;
+use std::path::Path;
+
+use crate::{AppConfig, create_bundle, restore_bundle};
+
+pub fn export_bundle_handler(
+    conn: &mut SqliteConnection,
+    output: &str,
+    config: &AppConfig,
+) -> Result<()> {
+    let output = Path::new(output);
+    let manifest = create_bundle(conn, config, output)?;
+
+    println!(
+        "Bundled {} notes ({} files, schema {}) into {}",
+        manifest.note_count,
+        manifest.files.len(),
+        manifest.schema_version,
+        output.display()
+    );
+    Ok(())
+}
+
+pub fn restore_handler(input: &str, force: bool, config: &AppConfig) -> Result<()> {
+    let manifest = restore_bundle(config, Path::new(input), force)?;
+
+    println!(
+        "Restored {} notes ({} files) from a bundle created at {}",
+        manifest.note_count,
+        manifest.files.len(),
+        manifest.created_at.format("%Y-%m-%d %H:%M:%S")
+    );
+    println!("Database: {}", config.paths.db_path);
+    println!("Notes: {}", config.paths.zettel_dir);
+    println!("Archive: {}", config.paths.archive_dir);
+    Ok(())
+}
@@ -0,0 +1,74 @@
+use anyhow::Result;
+use diesel::SqliteConnection;
+use std::{fs, path::Path};
+
+use crate::{
+    AppConfig, CompileNote, CompiledDocument, NoteType, ZettariumError, compile_document,
+    ensure_zettel_exists, parse_markdown,
+};
+
+pub fn compile_handler(
+    conn: &mut SqliteConnection,
+    id: &str,
+    output: Option<&str>,
+    config: &AppConfig,
+) -> Result<()> {
+    let compiled = compile_structure_note(conn, id, config)?;
+    let content = format!("{}\n", compiled.content.trim_end());
+
+    match output {
+        Some(output) => {
+            let path = Path::new(output);
+            fs::write(path, content).map_err(|e| ZettariumError::io(path, e))?;
+            println!(
+                "Compiled {} notes into {}",
+                compiled.notes.len(),
+                path.display()
+            );
+        }
+        None => print!("{}", content),
+    }
+    Ok(())
+}
+
+// Structure / Index ノートを1つの文書にまとめる（警告は標準エラーに出す）
+pub fn compile_structure_note(
+    conn: &mut SqliteConnection,
+    id: &str,
+    config: &AppConfig,
+) -> Result<CompiledDocument> {
+    let zettel = ensure_zettel_exists(conn, id)?;
+    if !matches!(zettel.type_, NoteType::Structure | NoteType::Index) {
+        anyhow::bail!(
+            "Only Structure or Index notes can be compiled (note {} is {})",
+            zettel.id,
+            zettel.type_
+        );
+    }
+
+    let compiled = compile_document(id, |id| load_compile_note(conn, id, config))?;
+    for warning in &compiled.warnings {
+        eprintln!("Warning: {}", warning);
+    }
+    Ok(compiled)
+}
+
+fn load_compile_note(
+    conn: &mut SqliteConnection,
+    id: &str,
+    config: &AppConfig,
+) -> Result<Option<CompileNote>, ZettariumError> {
+    let zettel = match ensure_zettel_exists(conn, id) {
+        Ok(zettel) => zettel,
+        Err(ZettariumError::NoteNotFound(_)) => return Ok(None),
+        Err(e) => return Err(e),
+    };
+    let parsed = parse_markdown(&zettel, config.paths.note_dir(zettel.archived).into())?;
+
+    Ok(Some(CompileNote {
+        id: zettel.id,
+        title: zettel.title,
+        type_: zettel.type_,
+        body: parsed.body,
+    }))
+}
@@ -0,0 +1,139 @@
+use anyhow::Result;
+use diesel::SqliteConnection;
+use std::{fs, path::Path};
+
+use crate::{
+    AppConfig, BibFormat, PandocMetadata, SitePage, ZettariumError, assets_dir, build_site,
+    compile_structure_note, dedup_and_warn, extract_citekeys, get_source, get_tag_by_zettel_id,
+    list_zettels, parse_markdown, render_pandoc_document, to_bibtex, to_csl_json,
+};
+
+pub fn export_pandoc_handler(
+    conn: &mut SqliteConnection,
+    id: &str,
+    output: &str,
+    bib_format: &str,
+    config: &AppConfig,
+) -> Result<()> {
+    let bib_format = bib_format.parse::<BibFormat>()?;
+    // リンク先のノートも含めた文書全体を対象にする
+    let compiled = compile_structure_note(conn, id, config)?;
+    let body = compiled.content;
+
+    // 本文中の引用を保存済みの書誌情報と突き合わせる
+    let mut sources = vec![];
+    for citekey in extract_citekeys(&body) {
+        match get_source(conn, &citekey)? {
+            Some(source) => sources.push(source),
+            None => eprintln!("Warning: Unknown citekey @{} was left as is.", citekey),
+        }
+    }
+
+    let output = Path::new(output);
+    let dir = output.parent().unwrap_or(Path::new(""));
+    let bibliography = if sources.is_empty() {
+        None
+    } else {
+        let (file, content) = match bib_format {
+            BibFormat::Bibtex => ("references.bib", to_bibtex(&sources)),
+            BibFormat::CslJson => ("references.json", to_csl_json(&sources) + "\n"),
+        };
+        let path = dir.join(file);
+        fs::write(&path, content).map_err(|e| ZettariumError::io(&path, e))?;
+        Some(file.to_string())
+    };
+
+    let metadata = PandocMetadata {
+        title: compiled.title.clone(),
+        bibliography: bibliography.clone(),
+        link_citations: true,
+    };
+    fs::write(output, render_pandoc_document(&metadata, &body))
+        .map_err(|e| ZettariumError::io(output, e))?;
+
+    println!("Exported {} to {}", id, output.display());
+    if let Some(file) = bibliography {
+        println!(
+            "Wrote {} references to {}",
+            sources.len(),
+            dir.join(file).display()
+        );
+    }
+    Ok(())
+}
+
+pub fn export_html_handler(
+    conn: &mut SqliteConnection,
+    outdir: &str,
+    tags: &Option<Vec<String>>,
+    config: &AppConfig,
+) -> Result<()> {
+    // tagを指定した場合はそのtagが付いたノートだけを公開する
+    let cleaned_tags = dedup_and_warn(tags.clone().unwrap_or_default());
+    let zettels = list_zettels(conn, None, None, &cleaned_tags, false, false)?;
+
+    let mut pages = vec![];
+    let mut assets = vec![];
+    for zettel in zettels {
+        let tags = get_tag_by_zettel_id(conn, &zettel.id)?
+            .into_iter()
+            .map(|t| t.tag_name)
+            .collect();
+        let parsed = match parse_markdown(&zettel, config.paths.zettel_dir.clone().into()) {
+            Ok(parsed) => parsed,
+            Err(ZettariumError::FileMissing(path)) => {
+                eprintln!(
+                    "Warning: Skipped {}: {} is missing",
+                    zettel.id,
+                    path.display()
+                );
+                continue;
+            }
+            Err(e) => return Err(e.into()),
+        };
+        let dir = assets_dir(config.paths.note_dir(zettel.archived), &zettel.id);
+        if dir.is_dir() {
+            assets.push((zettel.id.clone(), dir));
+        }
+        pages.push(SitePage {
+            id: zettel.id,
+            title: zettel.title,
+            type_: zettel.type_,
+            tags,
+            created_at: zettel.created_at,
+            body: parsed.body,
+        });
+    }
+
+    let outdir = Path::new(outdir);
+    for file in build_site(&pages) {
+        let path = outdir.join(&file.path);
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir).map_err(|e| ZettariumError::io(dir, e))?;
+        }
+        fs::write(&path, file.content).map_err(|e| ZettariumError::io(&path, e))?;
+    }
+    // 添付ファイルはノートのページからの相対リンク（./assets/<id>/）のまま参照できる場所に置く
+    for (id, dir) in &assets {
+        copy_assets(dir, &outdir.join("notes").join("assets").join(id))?;
+    }
+
+    println!(
+        "Exported {} notes to {}",
+        pages.len(),
+        outdir.join("index.html").display()
+    );
+    Ok(())
+}
+
+fn copy_assets(from: &Path, to: &Path) -> Result<(), ZettariumError> {
+    fs::create_dir_all(to).map_err(|e| ZettariumError::io(to, e))?;
+    for entry in fs::read_dir(from).map_err(|e| ZettariumError::io(from, e))? {
+        let path = entry.map_err(|e| ZettariumError::io(from, e))?.path();
+        if path.is_file() {
+            let dest = to.join(path.file_name().unwrap_or_default());
+            fs::copy(&path, &dest).map_err(|e| ZettariumError::io(&dest, e))?;
+        }
+    }
+    Ok(())
+}
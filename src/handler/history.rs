@@ -0,0 +1,152 @@
+use anyhow::Result;
+use diesel::SqliteConnection;
+use similar::TextDiff;
+
+use crate::{
+    AppConfig, Body, FrontMatter, GitAction, GitRepo, Markdown, Revision, ZettariumError,
+    apply_front_matter, auto_commit, ensure_type_rules, ensure_zettel_exists, get_revision,
+    list_revisions, note_paths, print_commits_as_table, print_revisions_as_table,
+    write_to_markdown,
+};
+
+pub fn history_handler(conn: &mut SqliteConnection, id: &str) -> Result<()> {
+    let zettel = ensure_zettel_exists(conn, id)?;
+    let revisions = list_revisions(conn, &zettel.id)?;
+
+    // Display
+    print_revisions_as_table(&revisions)?;
+    Ok(())
+}
+
+pub fn diff_handler(
+    conn: &mut SqliteConnection,
+    id: &str,
+    rev_a: Option<i32>,
+    rev_b: Option<i32>,
+) -> Result<()> {
+    let zettel = ensure_zettel_exists(conn, id)?;
+    let revisions = list_revisions(conn, &zettel.id)?;
+
+    // 省略時は最新とその1つ前を比べる
+    let Some(latest) = revisions.last() else {
+        println!("No revisions found.");
+        return Ok(());
+    };
+    let b = rev_b.unwrap_or(latest.revision);
+    let a = match rev_a {
+        Some(a) => a,
+        None if b > 1 => b - 1,
+        None => {
+            println!("Only one revision exists for {}.", zettel.id);
+            return Ok(());
+        }
+    };
+    let find = |number: i32| -> Result<&Revision, ZettariumError> {
+        revisions
+            .iter()
+            .find(|r| r.revision == number)
+            .ok_or_else(|| ZettariumError::RevisionNotFound {
+                id: zettel.id.clone(),
+                revision: number,
+            })
+    };
+    let (old, new) = (find(a)?, find(b)?);
+
+    let old_doc = old.document();
+    let new_doc = new.document();
+    let diff = TextDiff::from_lines(&old_doc, &new_doc);
+    let output = diff
+        .unified_diff()
+        .context_radius(3)
+        .header(
+            &format!("{}.md (rev {})", zettel.id, a),
+            &format!("{}.md (rev {})", zettel.id, b),
+        )
+        .to_string();
+
+    if output.is_empty() {
+        println!("No differences between revision {} and {}.", a, b);
+    } else {
+        print!("{}", output);
+    }
+    Ok(())
+}
+
+pub fn revert_handler(
+    conn: &mut SqliteConnection,
+    id: &str,
+    rev: i32,
+    config: &AppConfig,
+) -> Result<()> {
+    let zettel = ensure_zettel_exists(conn, id)?;
+    let revision =
+        get_revision(conn, &zettel.id, rev)?.ok_or_else(|| ZettariumError::RevisionNotFound {
+            id: zettel.id.clone(),
+            revision: rev,
+        })?;
+
+    let mut front_matter: FrontMatter =
+        serde_yaml::from_str(&revision.front_matter).map_err(|e| {
+            ZettariumError::FrontMatterInvalid {
+                path: format!("{} (rev {})", zettel.id, rev).into(),
+                line: e.location().map_or(1, |l| l.line()),
+                message: e.to_string(),
+            }
+        })?;
+
+    // 設定から消した種類はそのまま戻す（略称は種類の名前に直す）
+    match config.parse_type(front_matter.zettel.type_.name()) {
+        Ok(type_) => front_matter.zettel.type_ = type_,
+        Err(_) => eprintln!(
+            "Warning: type \"{}\" is not in the config, keeping it",
+            front_matter.zettel.type_.name()
+        ),
+    }
+
+    // 以前の状態に戻すだけなので、種類のルールを満たしていなくても警告だけにする
+    let type_ = &front_matter.zettel.type_;
+    if *type_ != zettel.type_ {
+        ensure_type_rules(
+            &config.type_rules(type_),
+            &zettel.id,
+            type_,
+            &front_matter.tags,
+            &revision.body,
+            true,
+        )?;
+    }
+
+    // タイトル・タグ・書誌情報はDBにも戻す（id / 作成日時 / アーカイブ状態は今のまま）
+    let updated = apply_front_matter(conn, &zettel, &front_matter)?;
+    let dir = config.paths.note_dir(updated.archived).to_string();
+    let markdown = Markdown {
+        front_matter: FrontMatter {
+            zettel: updated,
+            tags: front_matter.tags,
+            source: front_matter.source,
+        },
+        body: Body(revision.body),
+    };
+    write_to_markdown(conn, &markdown, dir.into())?;
+
+    println!("Reverted {} to revision {}.", zettel.id, rev);
+    auto_commit(config, GitAction::Revert, &markdown.front_matter.zettel);
+    Ok(())
+}
+
+pub fn log_handler(conn: &mut SqliteConnection, id: &str, config: &AppConfig) -> Result<()> {
+    let zettel = ensure_zettel_exists(conn, id)?;
+    // `z log` は読み取りだけなので auto_commit が無効でも使える
+    let Some(repo) = GitRepo::discover(config.paths.zettel_dir.as_ref())? else {
+        return Err(ZettariumError::Git(format!(
+            "{} is not a git repository",
+            config.paths.zettel_dir
+        ))
+        .into());
+    };
+    let commits = repo.log(&note_paths(config, &zettel.id))?;
+
+    // Display
+    print_commits_as_table(&commits)?;
+    Ok(())
+}
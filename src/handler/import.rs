@@ -0,0 +1,320 @@
+use anyhow::Result;
+use chrono::{Duration, Local, NaiveTime};
+use diesel::{Connection, SqliteConnection};
+use std::{collections::HashMap, fs, path::Path};
+
+use crate::{
+    AppConfig, BibFormat, Body, FrontMatter, ImportFormat, ImportedNote, Markdown, NewSource,
+    NoteType, Source, SourceMeta, ZettariumError, collect_markdown_files, create_source,
+    create_zettel, create_zettel_at, dedup_and_warn, ensure_zettel_exists, get_source,
+    list_zettels, load_template, parse_imported_note, parse_markdown, read_bibliography,
+    render_new_note, rewrite_wikilinks, upsert_source, write_to_markdown,
+};
+
+pub fn import_handler(
+    conn: &mut SqliteConnection,
+    dir: &str,
+    format: &str,
+    dry_run: bool,
+    config: &AppConfig,
+) -> Result<()> {
+    let format = format.parse::<ImportFormat>()?;
+    let dir = Path::new(dir);
+    if !dir.is_dir() {
+        anyhow::bail!("Import directory does not exist: {}", dir.display());
+    }
+
+    let mut report: Vec<String> = vec![];
+
+    // 1. 全ファイルを読み込む
+    let mut notes: Vec<ImportedNote> = vec![];
+    for path in collect_markdown_files(dir, format)? {
+        match parse_imported_note(&path, format, config) {
+            Ok(note) => notes.push(note),
+            Err(e) => report.push(format!("{}: skipped ({:#})", display_path(dir, &path), e)),
+        }
+    }
+
+    // 既存ノートと同じタイトルのものは二重登録しない
+    let existing = list_zettels(conn, None, None, &[], true, false)?;
+    let mut name_to_id: HashMap<String, String> = existing
+        .iter()
+        .map(|z| (z.title.to_lowercase(), z.id.clone()))
+        .collect();
+    notes.retain(|note| {
+        let duplicated = name_to_id.contains_key(&note.title.to_lowercase());
+        if duplicated {
+            report.push(format!(
+                "{}: skipped (a note titled \"{}\" already exists)",
+                display_path(dir, &note.source),
+                note.title
+            ));
+        }
+        !duplicated
+    });
+
+    // 2. Zettelを作成してwikilinkの名前→IDの対応表を作る
+    let mut ids: Vec<Option<String>> = vec![];
+    for note in &notes {
+        let tags = dedup_and_warn(note.tags.clone());
+        let id = if dry_run {
+            None
+        } else {
+            let mut created = note
+                .created_at
+                .unwrap_or_else(|| file_created_at(&note.source));
+            // `<日付>T000000` は `z daily` のIDなので、日付だけの作成日時は1秒ずらす
+            if created.time() == NaiveTime::MIN {
+                created += Duration::seconds(1);
+            }
+            let zettel = create_zettel_at(conn, &note.title, &note.type_, &tags, created)?;
+            Some(zettel.id)
+        };
+        // dry-runではIDがないので、リンク解決の確認用にタイトルを入れておく
+        let key = id.clone().unwrap_or_else(|| note.title.clone());
+
+        let mut names = vec![note.title.clone(), note.stem()];
+        names.extend(note.aliases.iter().cloned());
+        for name in names {
+            let name = name.to_lowercase();
+            if let Some(other) = name_to_id.get(&name) {
+                if *other != key {
+                    report.push(format!(
+                        "{}: link name \"{}\" is ambiguous, kept the first match",
+                        display_path(dir, &note.source),
+                        name
+                    ));
+                }
+                continue;
+            }
+            name_to_id.insert(name, key.clone());
+        }
+        ids.push(id);
+    }
+
+    // 3. wikilinkを書き換えてzettel_dirに書き出す
+    for (note, id) in notes.iter().zip(&ids) {
+        let source = display_path(dir, &note.source);
+        for warning in &note.warnings {
+            report.push(format!("{}: {}", source, warning));
+        }
+
+        let (body, unresolved) = rewrite_wikilinks(&note.body, |name| {
+            name_to_id.get(&name.to_lowercase()).cloned()
+        });
+        for link in unresolved {
+            report.push(format!("{}: unresolved link {}", source, link));
+        }
+
+        match id {
+            Some(id) => {
+                let zettel = ensure_zettel_exists(conn, id)?;
+                let markdown = Markdown {
+                    front_matter: FrontMatter {
+                        zettel,
+                        tags: dedup_and_warn(note.tags.clone()),
+                        source: None,
+                    },
+                    body: Body(body),
+                };
+                write_to_markdown(conn, &markdown, config.paths.zettel_dir.clone().into())?;
+            }
+            None => println!(
+                "Would import: {} -> \"{}\" ({}) [{}]",
+                source,
+                note.title,
+                note.type_,
+                note.tags.join(",")
+            ),
+        }
+    }
+
+    if dry_run {
+        println!("Dry run: {} notes would be imported.", notes.len());
+    } else {
+        println!("Imported {} notes from {}.", notes.len(), dir.display());
+    }
+
+    if !report.is_empty() {
+        println!("\nCould not map ({}):", report.len());
+        for line in &report {
+            println!("  {}", line);
+        }
+    }
+
+    Ok(())
+}
+
+fn display_path(root: &Path, path: &Path) -> String {
+    path.strip_prefix(root)
+        .unwrap_or(path)
+        .display()
+        .to_string()
+}
+
+// front matterに作成日時がない場合はファイルの更新日時を使う
+fn file_created_at(path: &Path) -> chrono::NaiveDateTime {
+    fs::metadata(path)
+        .and_then(|m| m.modified())
+        .map(|t| chrono::DateTime::<Local>::from(t).naive_local())
+        .unwrap_or_else(|_| Local::now().naive_local())
+}
+
+pub fn import_bib_handler(
+    conn: &mut SqliteConnection,
+    file: &str,
+    format: Option<&str>,
+    dry_run: bool,
+    config: &AppConfig,
+) -> Result<()> {
+    let path = Path::new(file);
+    let format = match format {
+        Some(format) => format.parse::<BibFormat>()?,
+        None => BibFormat::detect(path)?,
+    };
+    let entries = read_bibliography(path, format)?;
+
+    // Literatureテンプレートは1度だけ読み込む
+    let template = load_template(config, None, &NoteType::Literature)?;
+
+    let (mut created, mut updated, mut unchanged) = (0, 0, 0);
+    let mut report: Vec<String> = vec![];
+    for meta in &entries {
+        let existing = get_source(conn, &meta.citekey)?;
+        let linked = existing
+            .as_ref()
+            .and_then(|s| s.zettel_id.clone().map(|id| (s, id)));
+
+        let Some((existing, note_id)) = linked else {
+            // 対応するノートがまだないcitekeyはLiteratureノートを作る
+            let title = meta.title.clone().unwrap_or_else(|| meta.citekey.clone());
+            if dry_run {
+                println!("Would create: {} \"{}\"", meta.citekey, title);
+            } else {
+                // ノートと書誌情報は1件ずつまとめて作る
+                conn.transaction::<_, anyhow::Error, _>(|conn| {
+                    create_literature_note(
+                        conn,
+                        &title,
+                        meta,
+                        existing.is_some(),
+                        &template,
+                        config,
+                    )
+                })?;
+            }
+            created += 1;
+            continue;
+        };
+
+        // ゴミ箱にある・見つからないノートの書誌情報は更新しない
+        let zettel = match ensure_zettel_exists(conn, &note_id) {
+            Ok(zettel) => zettel,
+            Err(ZettariumError::NoteNotFound(_)) => {
+                report.push(format!(
+                    "{}: skipped (note {} is in the trash or missing)",
+                    meta.citekey, note_id
+                ));
+                continue;
+            }
+            Err(e) => return Err(e.into()),
+        };
+        let new_source = NewSource::from_meta(meta, &zettel.id, &zettel.title);
+        if same_source(existing, &new_source) {
+            unchanged += 1;
+            continue;
+        }
+        updated += 1;
+        if dry_run {
+            println!("Would update: {} ({})", meta.citekey, zettel.id);
+            continue;
+        }
+
+        conn.transaction::<_, anyhow::Error, _>(|conn| {
+            let source = upsert_source(conn, &new_source)?;
+
+            // ノート側のfront matterも書誌情報に合わせる
+            let dir = config.paths.note_dir(zettel.archived);
+            match parse_markdown(&zettel, dir.into()) {
+                Ok(parsed) => {
+                    let tags = parsed.front_matter.map(|f| f.tags).unwrap_or_default();
+                    let markdown = Markdown {
+                        front_matter: FrontMatter {
+                            zettel,
+                            tags,
+                            source: Some(SourceMeta::from(&source)),
+                        },
+                        body: Body(parsed.body.trim_start_matches(['\r', '\n']).to_string()),
+                    };
+                    write_to_markdown(conn, &markdown, dir.into())?;
+                }
+                Err(e) => report.push(format!(
+                    "{}: front matter not updated ({})",
+                    meta.citekey, e
+                )),
+            }
+            Ok(())
+        })?;
+    }
+
+    if dry_run {
+        println!(
+            "Dry run: {} would be created, {} updated, {} unchanged.",
+            created, updated, unchanged
+        );
+    } else {
+        println!(
+            "Imported {} entries from {}: {} created, {} updated, {} unchanged.",
+            entries.len(),
+            path.display(),
+            created,
+            updated,
+            unchanged
+        );
+    }
+
+    if !report.is_empty() {
+        println!("\nWarnings ({}):", report.len());
+        for line in &report {
+            println!("  {}", line);
+        }
+    }
+
+    Ok(())
+}
+
+fn create_literature_note(
+    conn: &mut SqliteConnection,
+    title: &str,
+    meta: &SourceMeta,
+    source_exists: bool,
+    template: &str,
+    config: &AppConfig,
+) -> Result<()> {
+    let zettel = create_zettel(conn, title, &NoteType::Literature, &[])?;
+    let new_source = NewSource::from_meta(meta, &zettel.id, &zettel.title);
+    if source_exists {
+        upsert_source(conn, &new_source)?;
+    } else {
+        create_source(conn, &new_source)?;
+    }
+
+    let front_matter = FrontMatter {
+        zettel,
+        tags: vec![],
+        source: Some(meta.clone()),
+    };
+    let body = Body(render_new_note(template, &front_matter));
+    let markdown = Markdown { front_matter, body };
+    write_to_markdown(conn, &markdown, config.paths.zettel_dir.clone().into())?;
+    Ok(())
+}
+
+fn same_source(source: &Source, new_source: &NewSource) -> bool {
+    source.title == new_source.title
+        && source.authors == new_source.authors
+        && source.year == new_source.year
+        && source.publisher == new_source.publisher
+        && source.url == new_source.url
+        && source.doi == new_source.doi
+}
@@ -0,0 +1,117 @@
+use anyhow::Result;
+use chrono::{Duration, Local, NaiveDate, NaiveTime};
+use diesel::SqliteConnection;
+use std::collections::HashMap;
+
+use crate::{
+    AppConfig, Body, FrontMatter, GitAction, Markdown, NoteType, ZettariumError, Zettel,
+    auto_commit, create_zettel_at, dedup_and_warn, edit_with_editor, ensure_zettel_exists,
+    get_tag_by_zettel_id, get_trashed_zettel, journal_id, journal_link, journal_title,
+    list_zettels, load_daily_template, mark_zettel_processed, render_new_note, render_template,
+    render_weekly, update_markdown_file, write_to_markdown, zettel_edit_handler,
+};
+
+// 日付のタイトルか `[journal] tags` のタグがあれば日々の記録とみなす
+fn is_daily_note(
+    conn: &mut SqliteConnection,
+    zettel: &Zettel,
+    date: NaiveDate,
+    config: &AppConfig,
+) -> Result<bool> {
+    if zettel.title == journal_title(date) {
+        return Ok(true);
+    }
+    let tags = get_tag_by_zettel_id(conn, &zettel.id)?;
+    Ok(tags.iter().any(|t| {
+        config
+            .journal
+            .tags
+            .iter()
+            .any(|tag| tag.eq_ignore_ascii_case(&t.tag_name))
+    }))
+}
+
+pub fn daily_handler(
+    conn: &mut SqliteConnection,
+    date: Option<NaiveDate>,
+    config: &AppConfig,
+) -> Result<()> {
+    let date = date.unwrap_or_else(|| Local::now().date_naive());
+    let id = journal_id(date);
+
+    // 既にあればそのまま開く
+    match ensure_zettel_exists(conn, &id) {
+        Ok(zettel) if zettel.archived => {
+            return Err(ZettariumError::AlreadyArchived(id).into());
+        }
+        // 同じIDでも日々の記録でないノートは開かない
+        Ok(zettel) if !is_daily_note(conn, &zettel, date, config)? => {
+            anyhow::bail!(
+                "Note {} (\"{}\") is not a daily note, so the daily note for {} cannot be created.",
+                id,
+                zettel.title,
+                journal_title(date)
+            );
+        }
+        Ok(_) => return zettel_edit_handler(conn, &id, None, None, &None, config),
+        Err(ZettariumError::NoteNotFound(_)) => {}
+        Err(e) => return Err(e.into()),
+    }
+    // ゴミ箱に同じIDがあると別のIDで作られてしまう
+    if get_trashed_zettel(conn, &id).is_ok() {
+        anyhow::bail!(
+            "The daily note {} is in the trash. Restore it with `z trash restore {}`.",
+            id,
+            id
+        );
+    }
+
+    let template = load_daily_template(config)?;
+    let tags = dedup_and_warn(config.journal.tags.clone());
+    let zettel = create_zettel_at(
+        conn,
+        &journal_title(date),
+        &NoteType::Fleeting,
+        &tags,
+        date.and_time(NaiveTime::MIN),
+    )?;
+    // 日々の記録は `z process` の整理対象にしない
+    let zettel = mark_zettel_processed(conn, &zettel.id)?;
+
+    // 前後の日へのリンクを埋めてから通常の変数を置き換える
+    let links = HashMap::from([
+        ("yesterday", journal_link(date - Duration::days(1))),
+        ("tomorrow", journal_link(date + Duration::days(1))),
+    ]);
+    let template = render_template(&template, &links);
+    let front_matter = FrontMatter {
+        zettel: zettel.clone(),
+        tags,
+        source: None,
+    };
+    let body = Body(render_new_note(&template, &front_matter));
+    let markdown = Markdown { front_matter, body };
+
+    let dir = &config.paths.zettel_dir;
+    write_to_markdown(conn, &markdown, dir.into())?;
+
+    let edited_zettel = edit_with_editor(conn, &zettel.id, config)?;
+    let tags = get_tag_by_zettel_id(conn, &zettel.id)?
+        .into_iter()
+        .map(|t| t.tag_name)
+        .collect::<Vec<_>>();
+
+    update_markdown_file(conn, &edited_zettel, &tags, dir)?;
+    auto_commit(config, GitAction::New, &edited_zettel);
+
+    Ok(())
+}
+
+pub fn weekly_handler(conn: &mut SqliteConnection, date: Option<NaiveDate>) -> Result<()> {
+    let date = date.unwrap_or_else(|| Local::now().date_naive());
+    // アーカイブ済みのノートも含める
+    let zettels = list_zettels(conn, None, None, &[], true, false)?;
+
+    print!("{}", render_weekly(date, &zettels));
+    Ok(())
+}
@@ -1,3 +1,29 @@
+pub mod attachment;
+pub mod bundle;
+pub mod compile;
+pub mod export;
+pub mod history;
+pub mod import;
+pub mod journal;
+pub mod process;
+pub mod promote;
+pub mod reindex;
+pub mod source;
+pub mod trash;
+pub mod tui;
 pub mod zettel;
 
-pub use zettel::*;
\ No newline at end of file
+pub use attachment::*;
+pub use bundle::*;
+pub use compile::*;
+pub use export::*;
+pub use history::*;
+pub use import::*;
+pub use journal::*;
+pub use process::*;
+pub use promote::*;
+pub use reindex::*;
+pub use source::*;
+pub use trash::*;
+pub use tui::*;
+pub use zettel::*;
@@ -0,0 +1,246 @@
+use anyhow::Result;
+use diesel::SqliteConnection;
+use std::{
+    io::{Write, stdin, stdout},
+    path::PathBuf,
+};
+
+use crate::{
+    AppConfig, Body, Finder, FinderItem, FrontMatter, FuzzyFinder, GitAction, LinkFormat, Markdown,
+    NoteType, ZettariumError, Zettel, auto_commit, check_type_rules, confirm, ensure_zettel_exists,
+    get_tag_by_zettel_id, list_unprocessed_zettels, list_zettels, mark_zettel_processed,
+    parse_markdown, update_markdown_file, update_zettel, update_zettel_timestamp_only,
+    write_to_markdown, zettel_archive_handler, zettel_edit_handler, zettel_remove_handler,
+};
+
+// 未整理のFleetingノートを古い順に1件ずつ整理する
+pub fn process_handler(conn: &mut SqliteConnection, config: &AppConfig) -> Result<()> {
+    let inbox = list_unprocessed_zettels(conn)?;
+    if inbox.is_empty() {
+        println!("Inbox zero: no unprocessed fleeting notes.");
+        return Ok(());
+    }
+
+    let total = inbox.len();
+    let mut processed = 0;
+    for (index, zettel) in inbox.iter().enumerate() {
+        print_note(conn, zettel, index + 1, total, config)?;
+
+        loop {
+            let choice = prompt(
+                "[p]romote  [l]iterature  [s]tructure link  [a]rchive  [d]elete  [n]ext  [q]uit",
+            )?;
+            let result = match choice.as_str() {
+                "p" => convert(conn, zettel, NoteType::Permanent, config),
+                "l" => convert(conn, zettel, NoteType::Literature, config),
+                "s" => link_into_structure(conn, zettel, config),
+                "a" => archive(conn, zettel, config).map(|_| true),
+                "d" => {
+                    if confirm(&format!("Move note {} to the trash?", zettel.id), false)? {
+                        zettel_remove_handler(conn, &zettel.id, true, config).map(|_| true)
+                    } else {
+                        Ok(false)
+                    }
+                }
+                "n" | "" => break,
+                "q" => {
+                    println!("Processed {} of {} notes.", processed, total);
+                    return Ok(());
+                }
+                _ => {
+                    println!("Unknown choice: {}", choice);
+                    continue;
+                }
+            };
+
+            match result {
+                Ok(true) => {
+                    processed += 1;
+                    break;
+                }
+                // キャンセルした場合は同じノートでもう一度選ぶ
+                Ok(false) => {}
+                Err(e) => println!("Error: {:#}", e),
+            }
+        }
+    }
+
+    println!("Processed {} of {} notes.", processed, total);
+    Ok(())
+}
+
+fn print_note(
+    conn: &mut SqliteConnection,
+    zettel: &Zettel,
+    position: usize,
+    total: usize,
+    config: &AppConfig,
+) -> Result<()> {
+    let tags = get_tag_by_zettel_id(conn, &zettel.id)?
+        .into_iter()
+        .map(|t| format!("#{}", t.tag_name))
+        .collect::<Vec<_>>();
+    let body = match parse_markdown(zettel, config.paths.zettel_dir.clone().into()) {
+        Ok(parsed) => parsed.body,
+        Err(ZettariumError::FileMissing(path)) => format!("({} is missing)", path.display()),
+        Err(e) => return Err(e.into()),
+    };
+
+    println!();
+    println!("[{}/{}] {} {}", position, total, zettel.id, zettel.title);
+    println!(
+        "Created {}  {}",
+        zettel.created_at.format("%Y/%m/%d %H:%M"),
+        tags.join(" ")
+    );
+    println!("{}", "-".repeat(40));
+    println!("{}", body.trim());
+    println!("{}", "-".repeat(40));
+    Ok(())
+}
+
+fn prompt(message: &str) -> Result<String> {
+    print!("{}: ", message);
+    stdout().flush()?;
+
+    let mut input = String::new();
+    // 入力が終わった（EOF）場合は終了する
+    if stdin().read_line(&mut input)? == 0 {
+        return Ok("q".into());
+    }
+    Ok(input.trim().to_lowercase())
+}
+
+// 整理済みにしてfront matterにも記録する
+fn mark_processed(conn: &mut SqliteConnection, id: &str, config: &AppConfig) -> Result<Zettel> {
+    ensure_note_file(id, config)?;
+    let zettel = mark_zettel_processed(conn, id)?;
+    let tags = get_tag_by_zettel_id(conn, id)?
+        .into_iter()
+        .map(|t| t.tag_name)
+        .collect::<Vec<_>>();
+    update_markdown_file(conn, &zettel, &tags, &config.paths.zettel_dir)?;
+    Ok(zettel)
+}
+
+// ファイルがない場合はDBを更新しない
+fn ensure_note_file(id: &str, config: &AppConfig) -> Result<()> {
+    let path = PathBuf::from(format!("{}/{}.md", config.paths.zettel_dir, id));
+    if !path.exists() {
+        return Err(ZettariumError::FileMissing(path).into());
+    }
+    Ok(())
+}
+
+// 種類を変えてからエディタで書き直す
+fn convert(
+    conn: &mut SqliteConnection,
+    zettel: &Zettel,
+    type_: NoteType,
+    config: &AppConfig,
+) -> Result<bool> {
+    ensure_note_file(&zettel.id, config)?;
+    // ルールを満たしていない場合は確認してから変える
+    let tags = get_tag_by_zettel_id(conn, &zettel.id)?
+        .into_iter()
+        .map(|t| t.tag_name)
+        .collect::<Vec<_>>();
+    let parsed = parse_markdown(zettel, config.paths.zettel_dir.clone().into())?;
+    let violations = check_type_rules(&config.type_rules(&type_), &tags, &parsed.body);
+    if !violations.is_empty() {
+        println!("{} does not meet the rules for {}:", zettel.id, type_);
+        for violation in &violations {
+            println!("  - {}", violation);
+        }
+        if !confirm(&format!("Convert to {} anyway?", type_), false)? {
+            return Ok(false);
+        }
+    }
+
+    update_zettel(conn, &zettel.id, &zettel.title, &type_, &[])?;
+    // front matterの種類もここで書き換わる
+    mark_processed(conn, &zettel.id, config)?;
+    println!("Converted {} to {}", zettel.id, type_);
+    zettel_edit_handler(conn, &zettel.id, None, None, &None, config)?;
+    Ok(true)
+}
+
+fn archive(conn: &mut SqliteConnection, zettel: &Zettel, config: &AppConfig) -> Result<()> {
+    mark_processed(conn, &zettel.id, config)?;
+    zettel_archive_handler(conn, &zettel.id, config)
+}
+
+// Structureノートの末尾にリンクを追加する（Fleetingノートはそのまま残す）
+fn link_into_structure(
+    conn: &mut SqliteConnection,
+    zettel: &Zettel,
+    config: &AppConfig,
+) -> Result<bool> {
+    let query = prompt("Structure note (ID or title)")?;
+    if query.is_empty() {
+        return Ok(false);
+    }
+
+    let structures = list_zettels(conn, None, Some("structure"), &[], false, false)?;
+    let items = structures
+        .iter()
+        .map(|z| FinderItem {
+            id: z.id.clone(),
+            title: z.title.clone(),
+            tags: vec![],
+            line: z.id.clone(),
+        })
+        .collect::<Vec<_>>();
+    let index = match structures.iter().position(|z| z.id == query) {
+        Some(index) => Some(index),
+        None => FuzzyFinder {
+            limit: 1,
+            title_only: true,
+        }
+        .find(&items, &query)?
+        .first()
+        .copied(),
+    };
+    let Some(structure) = index.map(|i| &structures[i]) else {
+        println!("No structure note matched \"{}\".", query);
+        return Ok(false);
+    };
+    if !confirm(
+        &format!("Link into {} ({})?", structure.title, structure.id),
+        false,
+    )? {
+        return Ok(false);
+    }
+
+    let processed = mark_processed(conn, &zettel.id, config)?;
+    let structure = ensure_zettel_exists(conn, &structure.id)?;
+    let dir = &config.paths.zettel_dir;
+    let parsed = parse_markdown(&structure, dir.into())?;
+    // テンプレートの空の箇条書き（`- `）は置き換える
+    let existing = parsed.body.trim_start_matches('\n').trim_end();
+    let existing = existing.strip_suffix("\n-").unwrap_or(existing);
+    let body = format!(
+        "{}\n- {}\n",
+        existing,
+        LinkFormat::Markdown.format(&zettel.id, &zettel.title)
+    );
+    let updated = update_zettel_timestamp_only(conn, &structure.id)?;
+    let tags = get_tag_by_zettel_id(conn, &structure.id)?
+        .into_iter()
+        .map(|t| t.tag_name)
+        .collect();
+    let markdown = Markdown {
+        front_matter: FrontMatter {
+            zettel: updated.clone(),
+            tags,
+            source: parsed.front_matter.and_then(|f| f.source),
+        },
+        body: Body(body),
+    };
+    write_to_markdown(conn, &markdown, dir.into())?;
+    auto_commit(config, GitAction::Edit, &updated);
+
+    auto_commit(config, GitAction::Edit, &processed);
+    println!("Linked {} into {}", zettel.id, structure.id);
+    Ok(true)
+}
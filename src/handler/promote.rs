@@ -0,0 +1,73 @@
+use anyhow::Result;
+use diesel::SqliteConnection;
+
+use crate::{
+    AppConfig, Body, FrontMatter, GitAction, Markdown, Zettel, auto_commit, ensure_type_rules,
+    ensure_zettel_exists, get_tag_by_zettel_id, load_template, missing_template_sections,
+    parse_markdown, render_new_note, update_zettel, write_to_markdown,
+};
+
+pub fn promote_handler(
+    conn: &mut SqliteConnection,
+    id: &str,
+    type_: &str,
+    force: bool,
+    config: &AppConfig,
+) -> Result<()> {
+    let note_type = config.parse_type(type_)?;
+    let zettel = ensure_zettel_exists(conn, id)?;
+    let tags = get_tag_by_zettel_id(conn, id)?
+        .into_iter()
+        .map(|t| t.tag_name)
+        .collect::<Vec<_>>();
+    let dir = config.paths.note_dir(zettel.archived);
+    let parsed = parse_markdown(&zettel, dir.into())?;
+
+    // 種類ごとのルールを満たしていなければ変更しない
+    ensure_type_rules(
+        &config.type_rules(&note_type),
+        id,
+        &note_type,
+        &tags,
+        &parsed.body,
+        force,
+    )?;
+
+    // 新しい種類のテンプレートにある節のうち、本文にないものを末尾に足す
+    // テンプレートが読めなければ DB を変更する前に止める
+    let template = load_template(config, None, &note_type)?;
+    let mut front_matter = FrontMatter {
+        zettel: Zettel {
+            type_: note_type.clone(),
+            ..zettel.clone()
+        },
+        tags,
+        source: parsed.front_matter.and_then(|f| f.source),
+    };
+    let rendered = render_new_note(&template, &front_matter);
+    let sections = missing_template_sections(&rendered, &parsed.body, &zettel.title);
+    let mut body = parsed.body.trim_start_matches('\n').trim_end().to_string();
+    for section in &sections {
+        body.push_str("\n\n");
+        body.push_str(section.trim_end());
+    }
+    body.push('\n');
+
+    let updated = update_zettel(conn, id, &zettel.title, &note_type, &[])?;
+    front_matter.zettel = updated.clone();
+    let markdown = Markdown {
+        front_matter,
+        body: Body(body),
+    };
+    write_to_markdown(conn, &markdown, dir.into())?;
+
+    println!("Promoted {} from {} to {}", id, zettel.type_, note_type);
+    for section in &sections {
+        println!(
+            "Added section: {}",
+            section.lines().next().unwrap_or_default()
+        );
+    }
+    auto_commit(config, GitAction::Edit, &updated);
+    Ok(())
+}
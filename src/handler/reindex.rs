@@ -0,0 +1,226 @@
+use anyhow::{Context, Result};
+use chrono::Local;
+use diesel::SqliteConnection;
+use std::{
+    collections::{HashMap, HashSet},
+    fs,
+    path::{Path, PathBuf},
+};
+
+use crate::{
+    AppConfig, FrontMatter, dedup_and_warn, get_tag_by_zettel_id, list_trashed_zettels,
+    list_zettels, read_front_matter, rebuild_index,
+};
+
+pub fn reindex_handler(conn: &mut SqliteConnection, check: bool, config: &AppConfig) -> Result<()> {
+    let mut entries: Vec<FrontMatter> = vec![];
+    let mut problems: Vec<String> = vec![];
+    let mut warnings: Vec<String> = vec![];
+    let mut seen: HashMap<String, PathBuf> = HashMap::new();
+
+    // ゴミ箱のノートはアーカイブ状態をfront matterのまま引き継ぐ
+    let trash_dir = config.paths.trash_dir();
+    for (dir, archived) in [
+        (&config.paths.zettel_dir, Some(false)),
+        (&config.paths.archive_dir, Some(true)),
+        (&trash_dir, None),
+    ] {
+        for path in list_markdown_files(Path::new(dir))? {
+            let mut front_matter = match read_front_matter(&path) {
+                Ok((front_matter, _)) => front_matter,
+                Err(e) => {
+                    problems.push(format!("{:#}", e));
+                    continue;
+                }
+            };
+
+            // ファイル名とディレクトリを正とする
+            let stem = path
+                .file_stem()
+                .map(|s| s.to_string_lossy().to_string())
+                .unwrap_or_default();
+            if front_matter.zettel.id != stem {
+                warnings.push(format!(
+                    "{}: id \"{}\" does not match the file name, using \"{}\"",
+                    path.display(),
+                    front_matter.zettel.id,
+                    stem
+                ));
+                front_matter.zettel.id = stem.clone();
+            }
+            // 設定から消した種類はそのまま残す（略称は種類の名前に直す）
+            match config.parse_type(front_matter.zettel.type_.name()) {
+                Ok(type_) => front_matter.zettel.type_ = type_,
+                Err(_) => warnings.push(format!(
+                    "{}: type \"{}\" is not in the config, keeping it",
+                    path.display(),
+                    front_matter.zettel.type_.name()
+                )),
+            }
+            match archived {
+                Some(archived) => {
+                    front_matter.zettel.archived = archived;
+                    front_matter.zettel.deleted_at = None;
+                }
+                None => {
+                    let deleted_at = front_matter.zettel.deleted_at;
+                    front_matter.zettel.deleted_at =
+                        deleted_at.or_else(|| Some(Local::now().naive_local()));
+                }
+            }
+            front_matter.tags = dedup_and_warn(front_matter.tags);
+
+            if let Some(other) = seen.insert(stem.clone(), path.clone()) {
+                problems.push(format!(
+                    "{}: duplicate note id (also in {})",
+                    path.display(),
+                    other.display()
+                ));
+                continue;
+            }
+            entries.push(front_matter);
+        }
+    }
+
+    let differences = diff_index(conn, &entries)?;
+    for line in problems.iter().chain(&warnings) {
+        println!("! {}", line);
+    }
+    for line in &differences {
+        println!("{}", line);
+    }
+
+    if check {
+        if differences.is_empty() && problems.is_empty() && warnings.is_empty() {
+            println!("Index is up to date ({} notes).", entries.len());
+            return Ok(());
+        }
+        anyhow::bail!(
+            "Index is out of date: {} differences, {} problems",
+            differences.len(),
+            problems.len() + warnings.len()
+        );
+    }
+
+    // 読めないファイルがある状態で作り直すと、そのノートがDBから消えてしまう
+    if !problems.is_empty() {
+        anyhow::bail!(
+            "Reindex aborted: {} files could not be indexed. Fix them or run `z reindex --check`.",
+            problems.len()
+        );
+    }
+
+    rebuild_index(conn, &entries)?;
+    let archived = entries.iter().filter(|e| e.zettel.archived).count();
+    let trashed = entries
+        .iter()
+        .filter(|e| e.zettel.deleted_at.is_some())
+        .count();
+    println!(
+        "Reindexed {} notes ({} archived, {} in trash).",
+        entries.len(),
+        archived,
+        trashed
+    );
+    Ok(())
+}
+
+fn list_markdown_files(dir: &Path) -> Result<Vec<PathBuf>> {
+    if !dir.exists() {
+        return Ok(vec![]);
+    }
+
+    let mut files = vec![];
+    for entry in
+        fs::read_dir(dir).with_context(|| format!("Failed to read directory: {}", dir.display()))?
+    {
+        let path = entry?.path();
+        if path.is_file() && path.extension().is_some_and(|ext| ext == "md") {
+            files.push(path);
+        }
+    }
+    files.sort();
+    Ok(files)
+}
+
+// DBとMarkdownの差分を `+`（DBにない） `-`（ファイルがない） `~`（内容が違う）で返す
+fn diff_index(conn: &mut SqliteConnection, entries: &[FrontMatter]) -> Result<Vec<String>> {
+    let mut differences = vec![];
+    let mut rows: HashMap<String, _> = list_zettels(conn, None, None, &[], true, false)?
+        .into_iter()
+        .chain(list_trashed_zettels(conn, None)?)
+        .map(|z| (z.id.clone(), z))
+        .collect();
+
+    for entry in entries {
+        let file = entry.get_zettel();
+        let Some(row) = rows.remove(&file.id) else {
+            differences.push(format!(
+                "+ {} \"{}\" (not in database)",
+                file.id, file.title
+            ));
+            continue;
+        };
+
+        let mut changes = vec![];
+        if row.title != file.title {
+            changes.push(format!("title \"{}\" -> \"{}\"", row.title, file.title));
+        }
+        if row.type_ != file.type_ {
+            changes.push(format!("type {} -> {}", row.type_, file.type_));
+        }
+        if row.created_at != file.created_at {
+            changes.push(format!(
+                "created_at {} -> {}",
+                row.created_at, file.created_at
+            ));
+        }
+        if row.archived != file.archived {
+            changes.push(format!("archived {} -> {}", row.archived, file.archived));
+        }
+        if row.deleted_at.is_some() != file.deleted_at.is_some() {
+            changes.push(format!(
+                "in trash {} -> {}",
+                row.deleted_at.is_some(),
+                file.deleted_at.is_some()
+            ));
+        }
+        if row.processed_at.is_some() != file.processed_at.is_some() {
+            changes.push(format!(
+                "processed {} -> {}",
+                row.processed_at.is_some(),
+                file.processed_at.is_some()
+            ));
+        }
+
+        let db_tags: HashSet<String> = get_tag_by_zettel_id(conn, &row.id)?
+            .into_iter()
+            .map(|t| t.tag_name.to_lowercase())
+            .collect();
+        let file_tags: HashSet<String> =
+            entry.get_tags().iter().map(|t| t.to_lowercase()).collect();
+        if db_tags != file_tags {
+            let mut db_tags: Vec<_> = db_tags.into_iter().collect();
+            let mut file_tags: Vec<_> = file_tags.into_iter().collect();
+            db_tags.sort();
+            file_tags.sort();
+            changes.push(format!(
+                "tags [{}] -> [{}]",
+                db_tags.join(","),
+                file_tags.join(",")
+            ));
+        }
+
+        if !changes.is_empty() {
+            differences.push(format!("~ {}: {}", file.id, changes.join(", ")));
+        }
+    }
+
+    let mut missing: Vec<_> = rows.into_values().collect();
+    missing.sort_by(|a, b| a.id.cmp(&b.id));
+    for row in missing {
+        differences.push(format!("- {} \"{}\" (file missing)", row.id, row.title));
+    }
+
+    Ok(differences)
+}
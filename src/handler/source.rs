@@ -0,0 +1,35 @@
+use anyhow::Result;
+use diesel::SqliteConnection;
+
+use crate::{
+    ZettariumError, ensure_zettel_exists, get_source, get_trashed_zettel, list_sources,
+    print_sources_as_table,
+};
+
+pub fn source_list_handler(conn: &mut SqliteConnection) -> Result<()> {
+    let sources = list_sources(conn)?;
+
+    // Display
+    print_sources_as_table(&sources)?;
+    Ok(())
+}
+
+pub fn source_show_handler(conn: &mut SqliteConnection, citekey: &str) -> Result<()> {
+    let source =
+        get_source(conn, citekey)?.ok_or_else(|| ZettariumError::SourceNotFound(citekey.into()))?;
+
+    print!("{}", source);
+    if let Some(zettel_id) = &source.zettel_id {
+        // ノートがゴミ箱にある・見つからない場合も書誌情報は表示する
+        match ensure_zettel_exists(conn, zettel_id) {
+            Ok(zettel) => println!("Note Title: {}", zettel.title),
+            Err(ZettariumError::NoteNotFound(_)) => match get_trashed_zettel(conn, zettel_id) {
+                Ok(zettel) => println!("Note Title: {} (in trash)", zettel.title),
+                Err(ZettariumError::NoteNotFound(_)) => println!("Note Title: - (note is missing)"),
+                Err(e) => return Err(e.into()),
+            },
+            Err(e) => return Err(e.into()),
+        }
+    }
+    Ok(())
+}
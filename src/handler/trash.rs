@@ -0,0 +1,116 @@
+use anyhow::Result;
+use chrono::{Duration, Local};
+use diesel::{Connection, SqliteConnection};
+use std::{fs, path::PathBuf};
+
+use crate::{
+    AppConfig, GitAction, ZettariumError, auto_commit, confirm, get_tag_by_zettel_id,
+    get_trashed_zettel, list_trashed_zettels, move_assets, print_trashed_zettels_as_table,
+    remove_assets, remove_zettel, restore_trashed_zettel, update_markdown_file,
+};
+
+pub fn trash_list_handler(conn: &mut SqliteConnection) -> Result<()> {
+    let zettels = list_trashed_zettels(conn, None)?;
+
+    // Display
+    print_trashed_zettels_as_table(&zettels)?;
+    Ok(())
+}
+
+pub fn trash_restore_handler(
+    conn: &mut SqliteConnection,
+    id: &str,
+    config: &AppConfig,
+) -> Result<()> {
+    let trashed = get_trashed_zettel(conn, id)?;
+
+    let path_from = PathBuf::from(format!("{}/{}.md", config.paths.trash_dir(), id));
+    let dir = config.paths.note_dir(trashed.archived);
+    let path_to = PathBuf::from(format!("{}/{}.md", dir, id));
+
+    // ファイルがない場合はDBを更新しない
+    if !path_from.exists() {
+        return Err(ZettariumError::FileMissing(path_from).into());
+    }
+    if path_to.exists() {
+        anyhow::bail!(
+            "Cannot restore note {}: {} already exists",
+            id,
+            path_to.display()
+        );
+    }
+
+    // ファイルを戻してからDBを更新し、失敗したらファイルもゴミ箱に戻す
+    let trash_dir = config.paths.trash_dir();
+    fs::create_dir_all(dir).map_err(|e| ZettariumError::io(dir, e))?;
+    fs::rename(&path_from, &path_to).map_err(|e| ZettariumError::io(&path_from, e))?;
+    if let Err(e) = move_assets(&trash_dir, dir, id) {
+        let _ = fs::rename(&path_to, &path_from);
+        return Err(e.into());
+    }
+    let restored = match restore_trashed_zettel(conn, id) {
+        Ok(restored) => restored,
+        Err(e) => {
+            let _ = fs::rename(&path_to, &path_from);
+            let _ = move_assets(dir, &trash_dir, id);
+            return Err(e.into());
+        }
+    };
+
+    let tags = get_tag_by_zettel_id(conn, id)?
+        .into_iter()
+        .map(|t| t.tag_name)
+        .collect::<Vec<_>>();
+    update_markdown_file(conn, &restored, &tags, dir)?;
+
+    println!("Restored note {} from the trash.", id);
+    auto_commit(config, GitAction::Restore, &restored);
+    Ok(())
+}
+
+pub fn trash_empty_handler(
+    conn: &mut SqliteConnection,
+    older_than: Option<Duration>,
+    force: bool,
+    config: &AppConfig,
+) -> Result<()> {
+    let before = match older_than {
+        Some(age) => match Local::now().naive_local().checked_sub_signed(age) {
+            Some(before) => Some(before),
+            None => anyhow::bail!("--older-than is too far in the past"),
+        },
+        None => None,
+    };
+    let zettels = list_trashed_zettels(conn, before)?;
+    if zettels.is_empty() {
+        println!("Nothing to delete.");
+        return Ok(());
+    }
+
+    if !force
+        && !confirm(
+            &format!("Permanently delete {} notes from the trash?", zettels.len()),
+            false,
+        )?
+    {
+        println!("Cancelled.");
+        return Ok(());
+    }
+
+    for zettel in &zettels {
+        // ファイルを消せなかった場合はDBの削除も取り消す
+        conn.transaction::<_, ZettariumError, _>(|conn| {
+            remove_zettel(conn, &zettel.id)?;
+            remove_assets(&config.paths.trash_dir(), &zettel.id)?;
+            let path = PathBuf::from(format!("{}/{}.md", config.paths.trash_dir(), zettel.id));
+            if path.exists() {
+                fs::remove_file(&path).map_err(|e| ZettariumError::io(&path, e))?;
+            }
+            Ok(())
+        })?;
+        auto_commit(config, GitAction::Purge, zettel);
+    }
+
+    println!("Permanently deleted {} notes.", zettels.len());
+    Ok(())
+}
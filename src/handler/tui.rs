@@ -0,0 +1,102 @@
+use anyhow::Result;
+use diesel::SqliteConnection;
+use std::io::stdout;
+use termimad::crossterm::event::{self, Event, KeyEventKind};
+
+use crate::{
+    AppConfig, GitAction, TerminalGuard, TuiAction, TuiApp, TuiNote, add_note_tags, archive_note,
+    commit_note, copy_to_clipboard, draw, get_tag_by_zettel_id, list_zettels, parse_markdown,
+    presenter::create_custom_skin, zettel_edit_handler,
+};
+
+pub fn tui_handler(conn: &mut SqliteConnection, config: &AppConfig) -> Result<()> {
+    let mut app = TuiApp::new(load_tui_notes(conn, config)?);
+    let skin = create_custom_skin();
+    let mut terminal = TerminalGuard::enter()?;
+    let mut out = stdout();
+
+    loop {
+        draw(&mut out, &app, &skin)?;
+
+        let Event::Key(key) = event::read()? else {
+            // リサイズなどは再描画だけ
+            continue;
+        };
+        if key.kind != KeyEventKind::Press {
+            continue;
+        }
+        let Some(action) = app.handle_key(key) else {
+            continue;
+        };
+
+        // 各ハンドラの出力は次の再描画で消えるので、結果はステータス行に出す
+        let result = match &action {
+            TuiAction::Quit => break,
+            TuiAction::Edit(id) => {
+                terminal.suspend()?;
+                let result = zettel_edit_handler(conn, id, None, None, &None, config);
+                terminal.resume()?;
+                result.map(|_| format!("Saved {}", id))
+            }
+            // 画面を崩さないよう、表示しない版を呼んでgitの結果もステータス行に出す
+            TuiAction::Archive(id) => archive_note(conn, id, config).map(|zettel| {
+                with_commit(
+                    format!("Archived {}", id),
+                    commit_note(config, GitAction::Archive, &zettel),
+                )
+            }),
+            TuiAction::AddTags(id, tags) => {
+                add_note_tags(conn, id, tags.clone(), config).map(|zettel| {
+                    with_commit(
+                        format!("Tagged {} with {}", id, tags.join(", ")),
+                        commit_note(config, GitAction::Edit, &zettel),
+                    )
+                })
+            }
+            TuiAction::CopyLink(id) => {
+                let link = app
+                    .notes
+                    .iter()
+                    .find(|n| &n.zettel.id == id)
+                    .map(|n| n.link(config.links.format))
+                    .unwrap_or_default();
+                copy_to_clipboard(&link).map(|_| format!("Copied to clipboard: {}", link))
+            }
+        };
+
+        app.message = Some(match result {
+            Ok(message) => message,
+            Err(e) => format!("Error: {:#}", e),
+        });
+        if !matches!(action, TuiAction::CopyLink(_)) {
+            app.reload(load_tui_notes(conn, config)?);
+        }
+    }
+
+    Ok(())
+}
+
+fn with_commit(message: String, commit: Option<String>) -> String {
+    match commit {
+        Some(commit) => format!("{} · {}", message, commit),
+        None => message,
+    }
+}
+
+// アーカイブしていないノートを本文付きで読み込む（読めないファイルは本文なし）
+fn load_tui_notes(conn: &mut SqliteConnection, config: &AppConfig) -> Result<Vec<TuiNote>> {
+    let mut notes = vec![];
+    for zettel in list_zettels(conn, None, None, &[], false, false)? {
+        let tags = get_tag_by_zettel_id(conn, &zettel.id)?
+            .into_iter()
+            .map(|t| t.tag_name)
+            .collect();
+        let body = parse_markdown(&zettel, config.paths.zettel_dir.clone().into())
+            .map(|parsed| parsed.body)
+            .unwrap_or_default();
+        notes.push(TuiNote::new(zettel, tags, body));
+    }
+    // 新しいノートを上に
+    notes.sort_by(|a, b| b.zettel.id.cmp(&a.zettel.id));
+    Ok(notes)
+}
@@ -1,20 +1,19 @@
 use anyhow::{Context, Result};
 use arboard::Clipboard;
 use diesel::{Connection, SqliteConnection};
-use std::{
-    fs,
-    io::{Write, stdin, stdout},
-    path::PathBuf,
-};
+use std::{collections::HashMap, fs, path::PathBuf};
 
 use crate::{
-    AppConfig, Body, FrontMatter, Markdown, NoteType, archive_zettel, create_zettel,
-    dedup_and_warn, edit_with_editor, ensure_zettel_exists, find_zettel_by_title,
-    get_tag_by_zettel_id, list_zettels,
-    presenter::{ensure_fzf_installed, run_fzf, view_markdown_with_style},
-    print_zettels_as_table, remove_zettel,
+    AppConfig, Body, DAILY_TEMPLATE, DAILY_TEMPLATE_FILE, FrontMatter, GitAction, LinkFormat,
+    Markdown, NewSource, NoteType, SourceMeta, ZettariumError, Zettel, archive_zettel, auto_commit,
+    confirm, create_source, create_zettel, dedup_and_warn, default_template, edit_with_editor,
+    ensure_type_rules, ensure_zettel_exists, find_zettel_by_title, get_source,
+    get_tag_by_zettel_id, list_zettels, load_template, move_assets, parse_markdown,
+    presenter::{Finder, FinderItem, FuzzyFinder, FzfFinder, view_markdown_with_style},
+    print_zettels_as_table, render_template, rewrite_markdown_file,
     store::run_migrations,
-    update_markdown_file, update_zettel, write_to_markdown,
+    template_file_name, template_uses, trash_zettel, update_markdown_file, update_zettel,
+    write_to_markdown,
 };
 
 pub fn init_handler(config: &AppConfig) -> Result<()> {
@@ -24,6 +23,20 @@ pub fn init_handler(config: &AppConfig) -> Result<()> {
     fs::create_dir_all(&config.paths.zettel_dir)?;
     fs::create_dir_all(&config.paths.archive_dir)?;
 
+    // NoteTypeごとの初期テンプレート（既存のものは上書きしない）
+    let template_dir = PathBuf::from(&config.templates.dir);
+    fs::create_dir_all(&template_dir)?;
+    for type_ in config.note_types() {
+        let path = template_dir.join(template_file_name(&type_));
+        if !path.exists() {
+            fs::write(&path, default_template(&type_))?;
+        }
+    }
+    let path = template_dir.join(DAILY_TEMPLATE_FILE);
+    if !path.exists() {
+        fs::write(&path, DAILY_TEMPLATE)?;
+    }
+
     // DBファイル作成とテーブル初期化
     let db_path: PathBuf = config.paths.db_path.clone().into(); // 例: ~/.local/share/zettarium/zettarium.db
     if !db_path.exists() {
@@ -38,13 +51,44 @@ pub fn init_handler(config: &AppConfig) -> Result<()> {
     Ok(())
 }
 
+// `z new` のテンプレート・本文とエディタを開くかどうか
+pub struct NewNoteOptions<'a> {
+    pub template: Option<&'a str>,
+    // テンプレートの {{body}}（なければ末尾）に入れる本文
+    pub body: Option<String>,
+    // falseならエディタを開かずに保存する（スクリプトからの取り込み用）
+    pub edit: bool,
+}
+
 pub fn zettel_new_handler(
     conn: &mut SqliteConnection,
     title: &str,
     type_: &str,
     tags: &Option<Vec<String>>,
+    source: Option<SourceMeta>,
+    options: NewNoteOptions,
     config: &AppConfig,
 ) -> Result<()> {
+    let note_type = config.parse_type(type_)?;
+
+    // 書誌情報はLiteratureノートにだけ付けられる
+    if let Some(meta) = &source {
+        if note_type != NoteType::Literature {
+            return Err(ZettariumError::InvalidSource(format!(
+                "--citekey can only be used with literature notes (got {})",
+                note_type
+            ))
+            .into());
+        }
+        if get_source(conn, &meta.citekey)?.is_some() {
+            return Err(ZettariumError::InvalidSource(format!(
+                "citekey {} already exists",
+                meta.citekey
+            ))
+            .into());
+        }
+    }
+
     // tag重複確認
     let mut tags_str: Vec<String> = vec![];
     if let Some(tags) = tags {
@@ -52,34 +96,110 @@ pub fn zettel_new_handler(
     }
     let cleaned_tags = dedup_and_warn(tags_str);
 
+    // テンプレートはZettel作成前に読み込んでおく（存在しない場合に空のノートを作らない）
+    let template = load_template(config, options.template, &note_type)?;
+
     // Zettel構造体にマッピングしてSQLiteに保存
-    let zettel = create_zettel(conn, title, type_, &cleaned_tags)?;
+    let zettel = create_zettel(conn, title, &note_type, &cleaned_tags)?;
+    if let Some(meta) = &source {
+        create_source(conn, &NewSource::from_meta(meta, &zettel.id, &zettel.title))?;
+    }
 
     // FrontMatter構造体にマッピング
     let front_matter = FrontMatter {
         zettel: zettel.clone(),
         tags: cleaned_tags.clone(),
+        source,
     };
 
     // MarkdownのBody生成
-    let body = Body(format!("## {}", front_matter.get_zettel().title));
+    let mut content = render_new_note(&template, &front_matter);
+    if template_uses(&template, "body") {
+        let body = HashMap::from([("body", options.body.unwrap_or_default())]);
+        content = render_template(&content, &body);
+    } else if let Some(text) = options.body {
+        content = format!("{}\n\n{}\n", content.trim_end(), text.trim_end());
+    }
+    let body = Body(content);
 
     // Markdown構造体にマッピング
     let markdown = Markdown { front_matter, body };
 
     // Markdownファイルの生成
     let dir = &config.paths.zettel_dir;
-    write_to_markdown(&markdown, dir.into())?;
+    write_to_markdown(conn, &markdown, dir.into())?;
 
-    // エディタを開いて編集
+    // スクリプトから使えるよう、標準出力にはIDだけを出す
+    if !options.edit {
+        auto_commit(config, GitAction::New, &zettel);
+        println!("{}", zettel.id);
+        return Ok(());
+    }
+
+    // エディタを開いて編集（front matterの変更はDBに反映済み）
     let edited_zettel = edit_with_editor(conn, &zettel.id, config)?;
+    let tags = get_tag_by_zettel_id(conn, &zettel.id)?
+        .into_iter()
+        .map(|t| t.tag_name)
+        .collect::<Vec<_>>();
 
-    // 編集後のタグは変更なしなので再利用
-    update_markdown_file(&edited_zettel, &cleaned_tags, dir)?;
+    update_markdown_file(conn, &edited_zettel, &tags, dir)?;
+    auto_commit(config, GitAction::New, &edited_zettel);
+    println!("{}", edited_zettel.id);
 
     Ok(())
 }
 
+pub fn render_new_note(template: &str, front_matter: &FrontMatter) -> String {
+    let zettel = front_matter.get_zettel();
+    let mut variables = HashMap::from([
+        ("title", zettel.title.clone()),
+        ("id", zettel.id.clone()),
+        ("type", zettel.type_.to_string()),
+        ("date", zettel.created_at.format("%Y-%m-%d").to_string()),
+        ("time", zettel.created_at.format("%H:%M").to_string()),
+        (
+            "tags",
+            front_matter
+                .get_tags()
+                .iter()
+                .map(|t| format!("#{}", t))
+                .collect::<Vec<_>>()
+                .join(" "),
+        ),
+    ]);
+
+    if let Some(source) = front_matter.get_source() {
+        let optional = |value: &Option<String>| value.clone().unwrap_or_default();
+        variables.extend([
+            ("citekey", source.citekey.clone()),
+            (
+                "source_title",
+                source.title.clone().unwrap_or_else(|| zettel.title.clone()),
+            ),
+            ("authors", source.authors.join("; ")),
+            (
+                "year",
+                source.year.map(|y| y.to_string()).unwrap_or_default(),
+            ),
+            ("publisher", optional(&source.publisher)),
+            ("url", optional(&source.url)),
+            ("doi", optional(&source.doi)),
+        ]);
+    }
+
+    // クリップボードは使われている場合だけ読む（ヘッドレス環境では読めない）
+    if template_uses(template, "clipboard") {
+        let text = read_clipboard().unwrap_or_else(|e| {
+            eprintln!("Warning: Could not read the clipboard: {}", e);
+            String::new()
+        });
+        variables.insert("clipboard", text);
+    }
+
+    render_template(template, &variables)
+}
+
 pub fn zettel_list_handler(
     conn: &mut SqliteConnection,
     id: Option<&str>,
@@ -87,6 +207,7 @@ pub fn zettel_list_handler(
     tags: &Option<Vec<String>>,
     all: bool,
     archived: bool,
+    config: &AppConfig,
 ) -> Result<()> {
     // tag重複確認
     let mut tags_str: Vec<String> = vec![];
@@ -95,8 +216,18 @@ pub fn zettel_list_handler(
     }
     let cleaned_tags = dedup_and_warn(tags_str);
 
+    // 略称や設定で追加した種類の alias もDBの値に直す
+    let note_type = type_.map(|t| config.parse_type(t)).transpose()?;
+
     // Zettel一覧の取得
-    let zettels = list_zettels(conn, id, type_, &cleaned_tags, all, archived)?;
+    let zettels = list_zettels(
+        conn,
+        id,
+        note_type.as_ref().map(NoteType::name),
+        &cleaned_tags,
+        all,
+        archived,
+    )?;
 
     // Display
     print_zettels_as_table(conn, &zettels)?;
@@ -121,7 +252,8 @@ pub fn zettel_edit_handler(
             .map(|t| t.tag_name)
             .collect::<Vec<_>>();
 
-        update_markdown_file(&updated, &tags, &config.paths.zettel_dir)?;
+        update_markdown_file(conn, &updated, &tags, &config.paths.zettel_dir)?;
+        auto_commit(config, GitAction::Edit, &updated);
         return Ok(());
     }
 
@@ -133,14 +265,30 @@ pub fn zettel_edit_handler(
     let merged_tags = merge_tags(conn, id, tags.clone())?;
 
     let final_title = title.unwrap_or(&existing_zettel.title);
-    let final_type = type_
-        .map(|t| t.to_string())
-        .unwrap_or_else(|| format!("{:?}", existing_zettel.type_));
+    let final_type = match type_ {
+        Some(t) => config.parse_type(t)?,
+        None => existing_zettel.type_.clone(),
+    };
+    // アーカイブ済みのノートもその場所で読み書きする
+    let dir = config.paths.note_dir(existing_zettel.archived);
+    // 種類を変える場合はルールを確かめる（無視するには `z promote --force` を使う）
+    if final_type != existing_zettel.type_ {
+        let parsed = parse_markdown(&existing_zettel, dir.into())?;
+        ensure_type_rules(
+            &config.type_rules(&final_type),
+            id,
+            &final_type,
+            &merged_tags,
+            &parsed.body,
+            false,
+        )?;
+    }
 
     let updated_zettel = update_zettel(conn, id, final_title, &final_type, &merged_tags)?;
 
     // Markdown更新処理
-    update_markdown_file(&updated_zettel, &merged_tags, &config.paths.zettel_dir)?;
+    update_markdown_file(conn, &updated_zettel, &merged_tags, dir)?;
+    auto_commit(config, GitAction::Edit, &updated_zettel);
 
     Ok(())
 }
@@ -174,30 +322,48 @@ pub fn zettel_archive_handler(
     id: &str,
     config: &AppConfig,
 ) -> Result<()> {
-    let archived_zettel = archive_zettel(conn, &id)?;
+    let archived_zettel = archive_note(conn, id, config)?;
 
-    if archived_zettel.archived {
-        println!("Note {} is already archived.", archived_zettel.id);
-        return Ok(());
+    println!("Archived note: {:?}", archived_zettel.id);
+    auto_commit(config, GitAction::Archive, &archived_zettel);
+    Ok(())
+}
+
+// ノートとその添付ファイルをarchive_dirへ移す（表示はしない。TUIからも使う）
+pub fn archive_note(conn: &mut SqliteConnection, id: &str, config: &AppConfig) -> Result<Zettel> {
+    let zettel = ensure_zettel_exists(conn, id)?;
+    if zettel.archived {
+        return Err(ZettariumError::AlreadyArchived(zettel.id).into());
     }
 
-    fs::create_dir_all(&config.paths.archive_dir)?; // 必要なら作成
-    let path_from = PathBuf::from(format!(
-        "{}/{}.md",
-        &config.paths.zettel_dir, archived_zettel.id
-    ));
-    let path_to = PathBuf::from(format!(
-        "{}/{}.md",
-        &config.paths.archive_dir, archived_zettel.id
-    ));
+    let path_from = PathBuf::from(format!("{}/{}.md", &config.paths.zettel_dir, zettel.id));
+    let path_to = PathBuf::from(format!("{}/{}.md", &config.paths.archive_dir, zettel.id));
 
+    // ファイルがない場合はDBを更新しない
     if !path_from.exists() {
-        anyhow::bail!("Zettel file does not exist: {}", path_from.display());
+        return Err(ZettariumError::FileMissing(path_from).into());
     }
-    fs::rename(&path_from, &path_to)?; // 失敗時は io::Error を伝搬
 
-    println!("Archived note: {:?}", archived_zettel.id);
-    Ok(())
+    fs::create_dir_all(&config.paths.archive_dir)
+        .map_err(|e| ZettariumError::io(&config.paths.archive_dir, e))?; // 必要なら作成
+    let archived_zettel = archive_zettel(conn, &id)?;
+    fs::rename(&path_from, &path_to).map_err(|e| ZettariumError::io(&path_from, e))?;
+    move_assets(&config.paths.zettel_dir, &config.paths.archive_dir, id)?;
+    Ok(archived_zettel)
+}
+
+// tagを追加してDBとfront matterを更新する（表示はしない。TUIからも使う）
+pub fn add_note_tags(
+    conn: &mut SqliteConnection,
+    id: &str,
+    tags: Vec<String>,
+    config: &AppConfig,
+) -> Result<Zettel> {
+    let zettel = ensure_zettel_exists(conn, id)?;
+    let merged_tags = merge_tags(conn, id, Some(tags))?;
+    let updated = update_zettel(conn, id, &zettel.title, &zettel.type_, &merged_tags)?;
+    rewrite_markdown_file(conn, &updated, &merged_tags, &config.paths.zettel_dir)?;
+    Ok(updated)
 }
 
 pub fn zettel_remove_handler(
@@ -206,45 +372,89 @@ pub fn zettel_remove_handler(
     force: bool,
     config: &AppConfig,
 ) -> Result<()> {
-    let exist_zettel =
-        ensure_zettel_exists(conn, id).with_context(|| format!("Note not found: {}", id))?;
+    let exist_zettel = ensure_zettel_exists(conn, id)?;
+
+    if !force
+        && !confirm(
+            &format!("Move note {} ({}) to the trash?", id, exist_zettel.title),
+            false,
+        )?
+    {
+        println!("Cancelled.");
+        return Ok(());
+    }
 
-    if !force {
-        print!(
-            "Are you sure you want to delete note {} ({})? [y/N]: ",
-            id, exist_zettel.title
+    // すぐには消さずにゴミ箱へ移す（完全に消すのは `z trash empty`）
+    let path_from = PathBuf::from(format!(
+        "{}/{}.md",
+        config.paths.note_dir(exist_zettel.archived),
+        id
+    ));
+    // ファイルを移せなかった場合はDBを更新しない（ノートが一覧から消えないように）
+    let note_dir = config.paths.note_dir(exist_zettel.archived);
+    let trash_dir = config.paths.trash_dir();
+    let path_to = PathBuf::from(format!("{}/{}.md", trash_dir, id));
+    let moved = path_from.exists();
+    if moved {
+        fs::create_dir_all(&trash_dir).map_err(|e| ZettariumError::io(&trash_dir, e))?;
+        fs::rename(&path_from, &path_to).map_err(|e| ZettariumError::io(&path_from, e))?;
+        if let Err(e) = move_assets(note_dir, &trash_dir, id) {
+            let _ = fs::rename(&path_to, &path_from);
+            return Err(e.into());
+        }
+    } else {
+        println!(
+            "Warning: {} does not exist. Only the database was updated.",
+            path_from.display()
         );
-        stdout().flush()?;
-
-        let mut input = String::new();
-        stdin().read_line(&mut input)?;
-        let input = input.trim().to_lowercase();
+    }
 
-        if input != "y" && input != "yes" {
-            println!("Cancelled.");
-            return Ok(());
+    let trashed = match trash_zettel(conn, id) {
+        Ok(trashed) => trashed,
+        Err(e) => {
+            if moved {
+                let _ = fs::rename(&path_to, &path_from);
+                let _ = move_assets(&trash_dir, note_dir, id);
+            }
+            return Err(e.into());
         }
+    };
+
+    if moved {
+        // front matterにも削除日時を残す（reindexでゴミ箱の状態を復元できるように）
+        let tags = get_tag_by_zettel_id(conn, id)?
+            .into_iter()
+            .map(|t| t.tag_name)
+            .collect::<Vec<_>>();
+        update_markdown_file(conn, &trashed, &tags, &trash_dir)?;
     }
 
-    let deleted = remove_zettel(conn, id)?;
+    println!(
+        "Moved note {} to the trash. Restore it with `z trash restore {}`.",
+        id, id
+    );
+    auto_commit(config, GitAction::Remove, &trashed);
 
-    if deleted == 0 {
-        println!("Warning:  No note was deleted.");
-    } else {
-        let locations = vec![
-            PathBuf::from(format!("{}/{}.md", config.paths.zettel_dir, id)),
-            PathBuf::from(format!("{}/{}.md", config.paths.archive_dir, id)),
-        ];
-
-        for path in locations {
-            if path.exists() {
-                fs::remove_file(&path)
-                    .with_context(|| format!("Failed to delete file: {}", path.display()))?;
-            }
-        }
-        println!("Removed: Note {} has been removed.", id);
+    Ok(())
+}
+
+// アーカイブ済みも含めたノートの実際のパスを出力する（fzfのプレビューなどから使う）
+pub fn zettel_path_handler(
+    conn: &mut SqliteConnection,
+    id: &str,
+    config: &AppConfig,
+) -> Result<()> {
+    let zettel = ensure_zettel_exists(conn, id)?;
+    let path =
+        PathBuf::from(config.paths.note_dir(zettel.archived)).join(format!("{}.md", zettel.id));
+    if !path.exists() {
+        return Err(ZettariumError::FileMissing(path).into());
     }
 
+    let path = path
+        .canonicalize()
+        .map_err(|e| ZettariumError::io(&path, e))?;
+    println!("{}", path.display());
     Ok(())
 }
 
@@ -255,8 +465,8 @@ pub fn zettel_view_handler(
 ) -> Result<()> {
     // ファイルの存在確認
     let zettel = ensure_zettel_exists(conn, id)?;
-    // noteディレクトリのパスを取得 & ファイルパスを生成
-    let dir = &config.paths.zettel_dir;
+    // noteディレクトリのパスを取得 & ファイルパスを生成（アーカイブ済みは archive_dir）
+    let dir = config.paths.note_dir(zettel.archived);
 
     // Display
     view_markdown_with_style(&zettel, dir.into())?;
@@ -264,57 +474,101 @@ pub fn zettel_view_handler(
     Ok(())
 }
 
+// `--link` で出力するリンクの書式と出力先
+pub struct LinkOptions {
+    pub format: LinkFormat,
+    // trueならクリップボードを使わず標準出力に書く（クリップボードのない環境向け）
+    pub print: bool,
+}
+
 pub fn zettel_find_handler(
     conn: &mut SqliteConnection,
-    keyword: Option<&str>,
+    keywords: &[String],
     title_only: bool,
-    link: bool,
+    link: Option<LinkOptions>,
+    first: bool,
+    top: Option<usize>,
     config: &AppConfig,
 ) -> Result<()> {
-    // fzfコマンドの存在確認
-    ensure_fzf_installed()?;
+    let query = keywords.join(" ");
+
+    // --first / --top はfzfなしで順位付けして出力する
+    let finder: Box<dyn Finder> = match (first, top) {
+        (true, _) => Box::new(FuzzyFinder {
+            limit: 1,
+            title_only,
+        }),
+        (_, Some(limit)) => Box::new(FuzzyFinder { limit, title_only }),
+        // リンクはまとめて作れるよう複数選択にする
+        _ => Box::new(FzfFinder {
+            config,
+            multi: link.is_some(),
+        }),
+    };
+    let interactive = !first && top.is_none();
 
-    let zettels = if let Some(keyword) = keyword {
-        if title_only {
-            find_zettel_by_title(conn, keyword)?
-        } else {
-            list_zettels(conn, None, None, &[], false, false)?
-        }
+    // fzfではタイトルの絞り込みをDBで先に行う
+    let zettels = if interactive && title_only && !query.is_empty() {
+        find_zettel_by_title(conn, &query)?
     } else {
         list_zettels(conn, None, None, &[], false, false)?
     };
 
-    let choices: Vec<String> = zettels
-        .iter()
-        .map(|z| {
-            let short_type = match z.type_ {
-                NoteType::Fleeting => "📝 Fleeting",
-                NoteType::Permanent => "🧠 Permanent",
-                NoteType::Literature => "📚 Literature",
-                NoteType::Structure => "🏗 Structure",
-                NoteType::Index => "🗂 Index",
-            };
-
-            let tags = get_tag_by_zettel_id(conn, &z.id)
-                .unwrap_or_default()
-                .iter()
-                .map(|t| format!("#{}", t.tag_name))
-                .collect::<Vec<_>>()
-                .join(" ");
-
-            format!("{} {} | {} | {}", z.id, short_type, z.title, tags)
-        })
-        .collect();
-
-    if let Some(selected) = run_fzf(&choices, config)? {
-        if link {
-            let id = selected.split_whitespace().next().unwrap_or("");
-            let title = selected.split('-').last().unwrap_or("").trim();
-            let link_syntax = format!("[{}](./{}.md)", title, id);
-            copy_to_clipboard(&link_syntax)?;
-            println!("Copied to clipboard: {}", link_syntax);
+    let mut items: Vec<FinderItem> = vec![];
+    for z in zettels {
+        let short_type = format!("{} {}", config.type_emoji(&z.type_), z.type_);
+
+        let tags = get_tag_by_zettel_id(conn, &z.id)?
+            .into_iter()
+            .map(|t| t.tag_name)
+            .collect::<Vec<_>>();
+        let hashtags = tags
+            .iter()
+            .map(|t| format!("#{}", t))
+            .collect::<Vec<_>>()
+            .join(" ");
+
+        items.push(FinderItem {
+            line: format!("{} {} | {} | {}", z.id, short_type, z.title, hashtags),
+            id: z.id,
+            title: z.title,
+            tags,
+        });
+    }
+
+    let fzf_query = if interactive && title_only {
+        ""
+    } else {
+        &query
+    };
+    let selected = finder.find(&items, fzf_query)?;
+    if selected.is_empty() {
+        if !interactive {
+            eprintln!("No notes matched \"{}\".", query);
+        }
+        return Ok(());
+    }
+
+    if let Some(options) = link {
+        // 表示用の行ではなくDBのタイトルからリンクを作る
+        let mut links = vec![];
+        for index in selected {
+            let zettel = ensure_zettel_exists(conn, &items[index].id)?;
+            links.push(options.format.format(&zettel.id, &zettel.title));
+        }
+        let text = links.join("\n");
+        if options.print {
+            println!("{}", text);
         } else {
-            println!("Selected: {}", selected);
+            copy_to_clipboard(&text)
+                .context("Failed to access the clipboard (use --print to write links to stdout)")?;
+            println!("Copied to clipboard: {}", links.join(", "));
+        }
+    } else if interactive {
+        println!("Selected: {}", items[selected[0]].line);
+    } else {
+        for index in selected {
+            println!("{}", items[index].line);
         }
     }
 
@@ -326,3 +580,8 @@ pub fn copy_to_clipboard(text: &str) -> Result<()> {
     clipboard.set_text(text.to_string())?;
     Ok(())
 }
+
+pub fn read_clipboard() -> Result<String> {
+    let mut clipboard = Clipboard::new()?;
+    Ok(clipboard.get_text()?)
+}
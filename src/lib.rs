@@ -7,15 +7,30 @@ pub mod model;
 pub mod presenter;
 pub mod schema;
 pub mod store;
+pub mod tui;
 pub mod validate;
+pub mod vcs;
 
 pub use cli::*;
 pub use config::*;
 pub use error::*;
-pub use handler::zettel::*;
+pub use handler::{
+    attachment::*, bundle::*, compile::*, export::*, history::*, import::*, journal::*, process::*,
+    promote::*, reindex::*, source::*, trash::*, tui::*, zettel::*,
+};
 pub use markdown::*;
-pub use model::{Body, FrontMatter, Markdown, NoteType, Tag, Zettel, ZettelTag};
-pub use presenter::zettel::*;
+pub use model::{
+    Attachment, BUNDLE_FORMAT_VERSION, Body, Diagnostic, FrontMatter, Manifest, ManifestFile,
+    Markdown, NoteType, ParsedMarkdown, Revision, Source, SourceMeta, Tag, Zettel, ZettelTag,
+};
+pub use presenter::{
+    attachment::*, finder::*, fuzzy::*, prompt::*, revision::*, source::*, zettel::*,
+};
 pub use schema::*;
-pub use store::{db::establish_connection, tag::*, zettel::*, zettel_tag::*};
+pub use store::{
+    attachment::*, bundle::*, db::establish_connection, revision::*, source::*, tag::*, zettel::*,
+    zettel_tag::*,
+};
+pub use tui::*;
 pub use validate::*;
+pub use vcs::*;
@@ -1,26 +1,38 @@
 use anyhow::Result;
 use clap::Parser;
-use zettarium::{Cli, Commands, cli, establish_connection, load_config};
+use std::path::Path;
+use zettarium::{
+    Cli, Commands, ZettariumError, cli, establish_connection, exit_code_of, load_config,
+};
 
-fn main() -> Result<()> {
+fn main() {
     let cli = Cli::parse();
-    if matches!(cli.command, Commands::Init) {
-        // configが最低限必要ならここで読み込む（なければデフォルトでもOK）
-        let config = load_config().unwrap_or_else(|e| {
-            eprintln!("Failed to load config: {}", e);
-            std::process::exit(1);
-        });
+    if let Err(e) = run(cli) {
+        eprintln!("Error: {:#}", e);
+        std::process::exit(exit_code_of(&e));
+    }
+}
 
+fn run(cli: Cli) -> Result<()> {
+    // configが最低限必要ならここで読み込む
+    let config = load_config()?;
+
+    // restoreはDBがない状態からでも実行できる
+    if matches!(cli.command, Commands::Init | Commands::Restore { .. }) {
         cli::dispatch(cli, None, &config)?; // DB接続なしで渡す
         return Ok(());
     }
 
-    let config = load_config().unwrap_or_else(|e| {
-        eprintln!("Failed to load config: {}", e);
-        std::process::exit(1);
-    });
+    // 存在しないパスを開くと空のDBが作られてしまうので先に確認する
+    if !Path::new(&config.paths.db_path).exists() {
+        return Err(ZettariumError::ConfigInvalid(format!(
+            "Database not found at {}. Run `z init` first.",
+            config.paths.db_path
+        ))
+        .into());
+    }
 
-    let conn = &mut establish_connection(&config);
+    let conn = &mut establish_connection(&config)?;
     cli::dispatch(cli, Some(conn), &config)?;
 
     Ok(())
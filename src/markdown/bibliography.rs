@@ -0,0 +1,496 @@
+use serde_json::Value;
+use std::{collections::HashMap, fs, path::Path, str::FromStr};
+
+use crate::{Diagnostic, Source, SourceMeta, ZettariumError, model::split_authors};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BibFormat {
+    Bibtex,
+    CslJson,
+}
+
+impl FromStr for BibFormat {
+    type Err = ZettariumError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "bibtex" | "bib" => Ok(BibFormat::Bibtex),
+            "csl-json" | "csl" | "json" => Ok(BibFormat::CslJson),
+            _ => Err(ZettariumError::InvalidImportFormat(format!(
+                "{} (expected bibtex or csl-json)",
+                s
+            ))),
+        }
+    }
+}
+
+impl BibFormat {
+    // --format がなければ拡張子から判断する
+    pub fn detect(path: &Path) -> Result<Self, ZettariumError> {
+        let ext = path
+            .extension()
+            .map(|e| e.to_string_lossy().to_string())
+            .unwrap_or_default();
+        ext.parse::<BibFormat>().map_err(|_| {
+            ZettariumError::InvalidImportFormat(format!(
+                "{} (use --format bibtex or --format csl-json)",
+                path.display()
+            ))
+        })
+    }
+}
+
+pub fn read_bibliography(
+    path: &Path,
+    format: BibFormat,
+) -> Result<Vec<SourceMeta>, ZettariumError> {
+    let content = fs::read_to_string(path).map_err(|e| ZettariumError::io(path, e))?;
+    let parsed = match format {
+        BibFormat::Bibtex => parse_bibtex(&content),
+        BibFormat::CslJson => parse_csl_json(&content),
+    };
+    parsed.map_err(|d| ZettariumError::BibliographyInvalid {
+        path: path.to_path_buf(),
+        line: d.line,
+        message: d.message,
+    })
+}
+
+// @comment / @preamble / @string は読み飛ばす（@stringの展開はしない）
+pub fn parse_bibtex(content: &str) -> Result<Vec<SourceMeta>, Diagnostic> {
+    let bytes = content.as_bytes();
+    let mut entries = vec![];
+    let mut pos = 0;
+
+    while let Some(found) = content[pos..].find('@') {
+        let start = pos + found;
+        pos = start + 1;
+        let kind_end = scan_while(bytes, pos, |b| b.is_ascii_alphanumeric());
+        let kind = content[pos..kind_end].to_lowercase();
+        pos = skip_whitespace(bytes, kind_end);
+        // エントリ外の `@`（メールアドレスなど）はコメントとして扱う
+        let close = match bytes.get(pos) {
+            Some(b'{') if !kind.is_empty() => b'}',
+            Some(b'(') if !kind.is_empty() => b')',
+            _ => continue,
+        };
+        if matches!(kind.as_str(), "comment" | "preamble" | "string") {
+            pos = skip_balanced(content, pos, close)?;
+            continue;
+        }
+
+        pos += 1;
+        let key_end = scan_while(bytes, pos, |b| b != b',' && b != close);
+        let citekey = content[pos..key_end].trim().to_string();
+        if citekey.is_empty() {
+            return Err(error_at(content, start, "missing citekey".into()));
+        }
+        pos = key_end;
+
+        let mut fields: HashMap<String, String> = HashMap::new();
+        loop {
+            pos = skip_whitespace(bytes, pos);
+            match bytes.get(pos) {
+                None => {
+                    return Err(error_at(
+                        content,
+                        start,
+                        format!("unterminated entry {}", citekey),
+                    ));
+                }
+                Some(&b) if b == close => {
+                    pos += 1;
+                    break;
+                }
+                Some(b',') => {
+                    pos += 1;
+                    continue;
+                }
+                _ => {}
+            }
+
+            let name_end = scan_while(bytes, pos, |b| {
+                b.is_ascii_alphanumeric() || matches!(b, b'-' | b'_' | b':' | b'.')
+            });
+            if name_end == pos {
+                return Err(error_at(content, pos, "expected a field name".into()));
+            }
+            let name = content[pos..name_end].to_lowercase();
+            pos = skip_whitespace(bytes, name_end);
+            if bytes.get(pos) != Some(&b'=') {
+                return Err(error_at(
+                    content,
+                    pos,
+                    format!("expected '=' after {}", name),
+                ));
+            }
+            pos = skip_whitespace(bytes, pos + 1);
+
+            let (value, next) = read_value(content, pos, close)?;
+            pos = next;
+            fields.insert(name, clean_value(&value));
+        }
+
+        entries.push(meta_from_fields(citekey, &fields));
+    }
+
+    Ok(entries)
+}
+
+// `{...}` / `"..."` / 数値や@stringの名前を `#` で連結したもの
+fn read_value(content: &str, mut pos: usize, close: u8) -> Result<(String, usize), Diagnostic> {
+    let bytes = content.as_bytes();
+    let mut value = String::new();
+    loop {
+        match bytes.get(pos) {
+            Some(b'{') => {
+                let end = skip_balanced(content, pos, b'}')?;
+                value.push_str(&content[pos + 1..end - 1]);
+                pos = end;
+            }
+            Some(b'"') => {
+                let mut depth = 0;
+                let mut end = pos + 1;
+                loop {
+                    match bytes.get(end) {
+                        None => return Err(error_at(content, pos, "unterminated string".into())),
+                        Some(b'{') => depth += 1,
+                        Some(b'}') => depth -= 1,
+                        Some(b'"') if depth == 0 => break,
+                        _ => {}
+                    }
+                    end += 1;
+                }
+                value.push_str(&content[pos + 1..end]);
+                pos = end + 1;
+            }
+            Some(_) => {
+                let end = scan_while(bytes, pos, |b| {
+                    !b.is_ascii_whitespace() && b != b',' && b != b'#' && b != close
+                });
+                if end == pos {
+                    return Err(error_at(content, pos, "expected a field value".into()));
+                }
+                value.push_str(&content[pos..end]);
+                pos = end;
+            }
+            None => return Err(error_at(content, pos, "expected a field value".into())),
+        }
+
+        pos = skip_whitespace(bytes, pos);
+        if bytes.get(pos) == Some(&b'#') {
+            pos = skip_whitespace(bytes, pos + 1);
+            continue;
+        }
+        return Ok((value, pos));
+    }
+}
+
+// `open` の位置から対応する閉じ括弧の直後までを読み飛ばす
+fn skip_balanced(content: &str, open: usize, close: u8) -> Result<usize, Diagnostic> {
+    let bytes = content.as_bytes();
+    let opener = bytes[open];
+    let mut depth = 0;
+    for (i, &b) in bytes.iter().enumerate().skip(open) {
+        if b == opener {
+            depth += 1;
+        } else if b == close {
+            depth -= 1;
+            if depth == 0 {
+                return Ok(i + 1);
+            }
+        }
+    }
+    Err(error_at(content, open, "unbalanced braces".into()))
+}
+
+fn scan_while(bytes: &[u8], mut pos: usize, f: impl Fn(u8) -> bool) -> usize {
+    while pos < bytes.len() && f(bytes[pos]) {
+        pos += 1;
+    }
+    pos
+}
+
+fn skip_whitespace(bytes: &[u8], pos: usize) -> usize {
+    scan_while(bytes, pos, |b| b.is_ascii_whitespace())
+}
+
+fn error_at(content: &str, pos: usize, message: String) -> Diagnostic {
+    let pos = pos.min(content.len());
+    Diagnostic {
+        line: content[..pos].matches('\n').count() + 1,
+        message,
+    }
+}
+
+// 大文字保護の `{}` とよく使われるエスケープを外し、空白をまとめる
+fn clean_value(value: &str) -> String {
+    let value = value
+        .replace(['{', '}'], "")
+        .replace("\\&", "&")
+        .replace("\\%", "%")
+        .replace("\\_", "_")
+        .replace('~', " ");
+    value.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+fn meta_from_fields(citekey: String, fields: &HashMap<String, String>) -> SourceMeta {
+    let field = |names: &[&str]| {
+        names
+            .iter()
+            .filter_map(|n| fields.get(*n))
+            .find(|v| !v.is_empty())
+            .cloned()
+    };
+
+    SourceMeta {
+        citekey,
+        title: field(&["title"]),
+        authors: field(&["author", "editor"])
+            .map(|a| split_authors(&a))
+            .unwrap_or_default(),
+        year: field(&["year", "date"]).and_then(|y| parse_year(&y)),
+        publisher: field(&["publisher", "journal", "booktitle", "institution"]),
+        url: field(&["url"]),
+        doi: field(&["doi"]),
+    }
+}
+
+fn parse_year(value: &str) -> Option<i32> {
+    value.get(..4).and_then(|y| y.parse().ok())
+}
+
+pub fn parse_csl_json(content: &str) -> Result<Vec<SourceMeta>, Diagnostic> {
+    let root: Value = serde_json::from_str(content).map_err(|e| Diagnostic {
+        line: e.line(),
+        message: e.to_string(),
+    })?;
+    let items = match root {
+        Value::Array(items) => items,
+        // 1件だけのファイルも受け付ける
+        item @ Value::Object(_) => vec![item],
+        _ => {
+            return Err(Diagnostic {
+                line: 1,
+                message: "expected an array of CSL items".into(),
+            });
+        }
+    };
+
+    let mut entries = vec![];
+    for (index, item) in items.iter().enumerate() {
+        let text = |name: &str| {
+            item.get(name)
+                .and_then(|v| match v {
+                    Value::String(s) => Some(s.trim().to_string()),
+                    Value::Number(n) => Some(n.to_string()),
+                    _ => None,
+                })
+                .filter(|s| !s.is_empty())
+        };
+
+        let Some(citekey) = text("id") else {
+            return Err(Diagnostic {
+                line: 1,
+                message: format!("item #{} has no id", index + 1),
+            });
+        };
+
+        let authors = item
+            .get("author")
+            .or_else(|| item.get("editor"))
+            .and_then(Value::as_array)
+            .map(|names| names.iter().filter_map(csl_name).collect())
+            .unwrap_or_default();
+
+        // "issued": {"date-parts": [[2017, 1, 1]]} または {"raw": "2017"}
+        let year = item.get("issued").and_then(|issued| {
+            issued
+                .pointer("/date-parts/0/0")
+                .and_then(|y| match y {
+                    Value::Number(n) => n.as_i64().map(|y| y as i32),
+                    Value::String(s) => s.parse().ok(),
+                    _ => None,
+                })
+                .or_else(|| {
+                    issued
+                        .get("raw")
+                        .and_then(Value::as_str)
+                        .and_then(parse_year)
+                })
+        });
+
+        entries.push(SourceMeta {
+            citekey,
+            title: text("title"),
+            authors,
+            year,
+            publisher: text("publisher").or_else(|| text("container-title")),
+            url: text("URL"),
+            doi: text("DOI"),
+        });
+    }
+
+    Ok(entries)
+}
+
+// BibTeXと揃えて "Family, Given" の形にする
+fn csl_name(name: &Value) -> Option<String> {
+    if let Some(literal) = name.get("literal").and_then(Value::as_str) {
+        return Some(literal.to_string());
+    }
+    let family = name.get("family").and_then(Value::as_str)?;
+    match name.get("given").and_then(Value::as_str) {
+        Some(given) => Some(format!("{}, {}", family, given)),
+        None => Some(family.to_string()),
+    }
+}
+
+// 種類は保存していないので @misc として書き出す
+pub fn to_bibtex(sources: &[Source]) -> String {
+    let mut out = String::new();
+    for source in sources {
+        let mut fields = vec![
+            ("title", source.title.clone()),
+            ("author", source.authors.clone()),
+        ];
+        if let Some(year) = source.year {
+            fields.push(("year", year.to_string()));
+        }
+        for (name, value) in [
+            ("publisher", &source.publisher),
+            ("url", &source.url),
+            ("doi", &source.doi),
+        ] {
+            if let Some(value) = value {
+                fields.push((name, value.clone()));
+            }
+        }
+
+        out.push_str(&format!("@misc{{{},\n", source.citekey));
+        for (name, value) in fields.iter().filter(|(_, v)| !v.is_empty()) {
+            out.push_str(&format!("  {} = {{{}}},\n", name, value));
+        }
+        out.push_str("}\n\n");
+    }
+    out
+}
+
+pub fn to_csl_json(sources: &[Source]) -> String {
+    let items: Vec<Value> = sources
+        .iter()
+        .map(|source| {
+            let mut item = serde_json::json!({
+                "id": source.citekey,
+                "type": "document",
+                "title": source.title,
+            });
+            let authors: Vec<Value> = source
+                .author_list()
+                .iter()
+                .map(|name| match name.split_once(',') {
+                    Some((family, given)) => {
+                        serde_json::json!({"family": family.trim(), "given": given.trim()})
+                    }
+                    None => serde_json::json!({"literal": name}),
+                })
+                .collect();
+            if !authors.is_empty() {
+                item["author"] = Value::Array(authors);
+            }
+            if let Some(year) = source.year {
+                item["issued"] = serde_json::json!({"date-parts": [[year]]});
+            }
+            for (name, value) in [
+                ("publisher", &source.publisher),
+                ("URL", &source.url),
+                ("DOI", &source.doi),
+            ] {
+                if let Some(value) = value {
+                    item[name] = Value::String(value.clone());
+                }
+            }
+            item
+        })
+        .collect();
+
+    serde_json::to_string_pretty(&items).expect("CSL items are always serializable")
+}
+
+// Test
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_bibtex() {
+        let content = r#"
+@comment{exported by the reference manager}
+@book{ahrens2017,
+  title     = {How to Take {Smart} Notes},
+  author    = {Ahrens, Sönke and Doe, Jane},
+  year      = 2017,
+  publisher = "CreateSpace",
+}
+
+@article{luhmann1981, title = "Kommunikation mit " # {Zettelkästen}, date = {1981-05}}
+"#;
+
+        let entries = parse_bibtex(content).unwrap();
+        assert_eq!(entries.len(), 2);
+        assert_eq!(entries[0].citekey, "ahrens2017");
+        assert_eq!(entries[0].title.as_deref(), Some("How to Take Smart Notes"));
+        assert_eq!(entries[0].authors, vec!["Ahrens, Sönke", "Doe, Jane"]);
+        assert_eq!(entries[0].year, Some(2017));
+        assert_eq!(entries[0].publisher.as_deref(), Some("CreateSpace"));
+        assert_eq!(
+            entries[1].title.as_deref(),
+            Some("Kommunikation mit Zettelkästen")
+        );
+        assert_eq!(entries[1].year, Some(1981));
+
+        let err = parse_bibtex("@book{broken,\n  title = {unterminated\n").unwrap_err();
+        assert_eq!(err.line, 2);
+    }
+
+    #[test]
+    fn test_parse_csl_json() {
+        let content = r#"[{
+            "id": "ahrens2017",
+            "type": "book",
+            "title": "How to Take Smart Notes",
+            "author": [{"family": "Ahrens", "given": "Sönke"}],
+            "issued": {"date-parts": [[2017]]},
+            "DOI": "10.0000/example"
+        }]"#;
+
+        let entries = parse_csl_json(content).unwrap();
+        assert_eq!(entries.len(), 1);
+        assert_eq!(entries[0].authors, vec!["Ahrens, Sönke"]);
+        assert_eq!(entries[0].year, Some(2017));
+        assert_eq!(entries[0].doi.as_deref(), Some("10.0000/example"));
+    }
+
+    #[test]
+    fn test_bibliography_round_trip() {
+        let source = Source {
+            citekey: "ahrens2017".into(),
+            zettel_id: None,
+            title: "How to Take Smart Notes".into(),
+            authors: "Ahrens, Sönke and Doe, Jane".into(),
+            year: Some(2017),
+            publisher: Some("CreateSpace".into()),
+            url: None,
+            doi: None,
+        };
+
+        let bib = parse_bibtex(&to_bibtex(std::slice::from_ref(&source))).unwrap();
+        let csl = parse_csl_json(&to_csl_json(std::slice::from_ref(&source))).unwrap();
+        for meta in [&bib[0], &csl[0]] {
+            assert_eq!(meta.citekey, "ahrens2017");
+            assert_eq!(meta.authors, vec!["Ahrens, Sönke", "Doe, Jane"]);
+            assert_eq!(meta.year, Some(2017));
+            assert_eq!(meta.publisher.as_deref(), Some("CreateSpace"));
+        }
+    }
+}
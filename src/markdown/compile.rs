@@ -0,0 +1,240 @@
+use regex::Regex;
+use std::collections::HashSet;
+
+use crate::{
+    NoteType, ZettariumError, extract_note_links, replace_note_links, strip_title_heading,
+};
+
+// compileに必要なノートの情報
+#[derive(Debug, Clone)]
+pub struct CompileNote {
+    pub id: String,
+    pub title: String,
+    pub type_: NoteType,
+    pub body: String,
+}
+
+#[derive(Debug)]
+pub struct CompiledDocument {
+    pub title: String,
+    pub content: String,
+    // 取り込んだノートのID（文書内の順）
+    pub notes: Vec<String>,
+    pub warnings: Vec<String>,
+}
+
+pub fn note_anchor(id: &str) -> String {
+    format!("note-{}", id)
+}
+
+// Structure / Index ノートのリンクだけをたどり、リンク先の本文を順に埋め込む
+pub fn compile_document<F>(root_id: &str, mut load: F) -> Result<CompiledDocument, ZettariumError>
+where
+    F: FnMut(&str) -> Result<Option<CompileNote>, ZettariumError>,
+{
+    let Some(root) = load(root_id)? else {
+        return Err(ZettariumError::NoteNotFound(root_id.to_string()));
+    };
+
+    // 1. 文書に含めるノートと深さを決める
+    let mut order: Vec<(CompileNote, usize)> = vec![];
+    let mut visited: HashSet<String> = HashSet::new();
+    let mut warnings: Vec<String> = vec![];
+    let mut stack = vec![root.id.clone()];
+    walk(
+        root,
+        0,
+        &mut load,
+        &mut stack,
+        &mut visited,
+        &mut order,
+        &mut warnings,
+    )?;
+
+    // 2. 見出しを深さに合わせて下げ、ノート間のリンクをアンカーに置き換える
+    let included: HashSet<&str> = order.iter().map(|(note, _)| note.id.as_str()).collect();
+    let mut sections = vec![];
+    for (note, depth) in &order {
+        let heading = "#".repeat((depth + 1).min(6));
+        let body = strip_title_heading(note.body.trim(), &note.title);
+        let body = demote_headings(body.trim(), depth + 2);
+        let body = replace_note_links(&body, |text, id| {
+            if included.contains(id) {
+                format!("[{}](#{})", text, note_anchor(id))
+            } else {
+                text.to_string()
+            }
+        });
+
+        let mut section = format!(
+            "{} {} {{#{}}}\n",
+            heading,
+            note.title,
+            note_anchor(&note.id)
+        );
+        if !body.trim().is_empty() {
+            section.push('\n');
+            section.push_str(body.trim());
+            section.push('\n');
+        }
+        sections.push(section);
+    }
+
+    let (root, _) = &order[0];
+    Ok(CompiledDocument {
+        title: root.title.clone(),
+        content: sections.join("\n"),
+        notes: order.iter().map(|(note, _)| note.id.clone()).collect(),
+        warnings,
+    })
+}
+
+fn walk<F>(
+    note: CompileNote,
+    depth: usize,
+    load: &mut F,
+    stack: &mut Vec<String>,
+    visited: &mut HashSet<String>,
+    order: &mut Vec<(CompileNote, usize)>,
+    warnings: &mut Vec<String>,
+) -> Result<(), ZettariumError>
+where
+    F: FnMut(&str) -> Result<Option<CompileNote>, ZettariumError>,
+{
+    visited.insert(note.id.clone());
+    let expand = depth == 0 || matches!(note.type_, NoteType::Structure | NoteType::Index);
+    let links = if expand {
+        extract_note_links(&note.body)
+    } else {
+        vec![]
+    };
+    order.push((note, depth));
+
+    for id in links {
+        if stack.contains(&id) {
+            let mut cycle = stack.clone();
+            cycle.push(id);
+            warnings.push(format!("cycle detected: {}", cycle.join(" -> ")));
+            continue;
+        }
+        // 既に取り込んだノートはアンカーで参照する
+        if visited.contains(&id) {
+            continue;
+        }
+        match load(&id)? {
+            Some(child) => {
+                stack.push(id);
+                walk(child, depth + 1, load, stack, visited, order, warnings)?;
+                stack.pop();
+            }
+            None => warnings.push(format!("linked note {} was not found", id)),
+        }
+    }
+    Ok(())
+}
+
+// 一番浅い見出しが `level` になるようにずらす（コードブロック内は触らない）
+fn demote_headings(body: &str, level: usize) -> String {
+    let heading = Regex::new(r"^(#{1,6})(\s.*)?$").unwrap();
+    let mut in_fence = false;
+    let mut levels = vec![];
+    for line in body.lines() {
+        if line.trim_start().starts_with("```") {
+            in_fence = !in_fence;
+        } else if !in_fence && let Some(caps) = heading.captures(line) {
+            levels.push(caps[1].len());
+        }
+    }
+    let Some(min) = levels.into_iter().min() else {
+        return body.to_string();
+    };
+
+    in_fence = false;
+    body.lines()
+        .map(|line| {
+            if line.trim_start().starts_with("```") {
+                in_fence = !in_fence;
+            } else if !in_fence && let Some(caps) = heading.captures(line) {
+                let new_level = (caps[1].len() - min + level).min(6);
+                return format!(
+                    "{}{}",
+                    "#".repeat(new_level),
+                    caps.get(2).map_or("", |m| m.as_str())
+                );
+            }
+            line.to_string()
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+// Test
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+
+    fn note(id: &str, title: &str, type_: NoteType, body: &str) -> CompileNote {
+        CompileNote {
+            id: id.into(),
+            title: title.into(),
+            type_,
+            body: body.into(),
+        }
+    }
+
+    #[test]
+    fn test_compile_document() {
+        let notes = HashMap::from([
+            (
+                "20250101T000000",
+                note(
+                    "20250101T000000",
+                    "Paper",
+                    NoteType::Structure,
+                    "## Paper\n\n- [Intro](./20250101T000001.md)\n- [Part](./20250101T000002.md)\n",
+                ),
+            ),
+            (
+                "20250101T000001",
+                note(
+                    "20250101T000001",
+                    "Intro",
+                    NoteType::Permanent,
+                    "## Intro\n\n### Detail\n\nSee [Part](./20250101T000002.md) and [Other](./20250101T000009.md).\n",
+                ),
+            ),
+            (
+                "20250101T000002",
+                note(
+                    "20250101T000002",
+                    "Part",
+                    NoteType::Structure,
+                    "## Part\n\nBack to [Paper](./20250101T000000.md)\n",
+                ),
+            ),
+        ]);
+
+        let compiled =
+            compile_document("20250101T000000", |id| Ok(notes.get(id).cloned())).unwrap();
+
+        assert_eq!(
+            compiled.notes,
+            vec!["20250101T000000", "20250101T000001", "20250101T000002"]
+        );
+        assert!(
+            compiled
+                .content
+                .contains("## Intro {#note-20250101T000001}\n\n### Detail\n\nSee [Part](#note-20250101T000002) and Other.")
+        );
+        assert!(
+            compiled
+                .content
+                .contains("- [Intro](#note-20250101T000001)")
+        );
+        assert_eq!(
+            compiled.warnings,
+            vec!["cycle detected: 20250101T000000 -> 20250101T000002 -> 20250101T000000"]
+        );
+    }
+}
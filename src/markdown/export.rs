@@ -0,0 +1,67 @@
+use regex::Regex;
+use serde::Serialize;
+
+// Pandocに渡すYAMLメタデータ
+#[derive(Debug, Serialize)]
+pub struct PandocMetadata {
+    pub title: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub bibliography: Option<String>,
+    #[serde(rename = "link-citations")]
+    pub link_citations: bool,
+}
+
+pub fn render_pandoc_document(metadata: &PandocMetadata, body: &str) -> String {
+    let yaml = serde_yaml::to_string(metadata).unwrap_or_default();
+    let body = strip_title_heading(body.trim(), &metadata.title);
+    format!("---\n{}---\n\n{}\n", yaml, body.trim())
+}
+
+// タイトルはメタデータに入れるので、テンプレートの `## {{title}}` は外す
+pub fn strip_title_heading<'a>(body: &'a str, title: &str) -> &'a str {
+    let (first, rest) = body.split_once('\n').unwrap_or((body, ""));
+    let heading = first.trim_start_matches('#').trim();
+    // `# Title {#anchor}` のような見出し属性は無視する
+    let heading = match heading.rsplit_once(" {#") {
+        Some((text, attr)) if attr.ends_with('}') => text.trim(),
+        _ => heading,
+    };
+    if first.starts_with('#') && heading == title {
+        rest
+    } else {
+        body
+    }
+}
+
+// `[@key]` / `[see @a, p. 3; -@b]` のような括弧付きの引用からcitekeyを出現順に集める
+pub fn extract_citekeys(body: &str) -> Vec<String> {
+    let group = Regex::new(r"\[[^\[\]]*@[^\[\]]*\]").unwrap();
+    let key = Regex::new(r"(?:^|[\s;\[-])@([A-Za-z0-9_][\w:.#$%&+?<>~/-]*)").unwrap();
+
+    let mut keys: Vec<String> = vec![];
+    for caps in group.find_iter(body) {
+        for k in key.captures_iter(caps.as_str()) {
+            // 末尾の句読点はcitekeyに含めない
+            let citekey = k[1].trim_end_matches(['.', ':', ',']).to_string();
+            if !keys.contains(&citekey) {
+                keys.push(citekey);
+            }
+        }
+    }
+    keys
+}
+
+// Test
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_extract_citekeys() {
+        let body = "As argued [@ahrens2017, p. 33], notes need context \
+                    [see @luhmann1981; -@ahrens2017].\n\
+                    Mail me at me@example.com or [read this](./20250608T120000.md).";
+
+        assert_eq!(extract_citekeys(body), vec!["ahrens2017", "luhmann1981"]);
+    }
+}
@@ -0,0 +1,444 @@
+use chrono::NaiveDateTime;
+use pulldown_cmark::{Event, Options, Parser, html};
+use serde::Serialize;
+use std::{
+    collections::{BTreeMap, HashMap, HashSet},
+    path::PathBuf,
+};
+
+use crate::{NoteType, extract_note_links, replace_note_links, strip_title_heading};
+
+// サイトに載せるノート
+#[derive(Debug, Clone)]
+pub struct SitePage {
+    pub id: String,
+    pub title: String,
+    pub type_: NoteType,
+    pub tags: Vec<String>,
+    pub created_at: NaiveDateTime,
+    pub body: String,
+}
+
+// outdirからの相対パスと中身
+#[derive(Debug)]
+pub struct SiteFile {
+    pub path: PathBuf,
+    pub content: String,
+}
+
+#[derive(Debug, Serialize)]
+struct SearchEntry<'a> {
+    id: &'a str,
+    title: &'a str,
+    #[serde(rename = "type")]
+    type_: String,
+    tags: &'a [String],
+    url: String,
+    text: String,
+}
+
+const STYLE: &str = "body { max-width: 48rem; margin: 2rem auto; padding: 0 1rem; font-family: sans-serif; line-height: 1.6; color: #222; }
+nav a { margin-right: 1rem; }
+.meta { color: #666; font-size: 0.9rem; }
+.tag { margin-right: 0.5rem; }
+.backlinks { border-top: 1px solid #ddd; margin-top: 2rem; }
+pre { background: #f4f4f4; padding: 0.75rem; overflow-x: auto; }
+blockquote { border-left: 3px solid #ccc; margin-left: 0; padding-left: 1rem; color: #555; }
+#search { width: 100%; padding: 0.5rem; font-size: 1rem; }
+";
+
+const SEARCH_SCRIPT: &str = r#"const input = document.getElementById("search");
+const results = document.getElementById("results");
+fetch("search-index.json")
+  .then((response) => response.json())
+  .then((index) => {
+    input.addEventListener("input", () => {
+      const terms = input.value.toLowerCase().split(/\s+/).filter(Boolean);
+      results.innerHTML = "";
+      if (terms.length === 0) return;
+      index
+        .filter((note) => {
+          const haystack = [note.title, note.tags.join(" "), note.text].join(" ").toLowerCase();
+          return terms.every((term) => haystack.includes(term));
+        })
+        .slice(0, 50)
+        .forEach((note) => {
+          const item = document.createElement("li");
+          const link = document.createElement("a");
+          link.href = note.url;
+          link.textContent = note.title;
+          item.appendChild(link);
+          results.appendChild(item);
+        });
+    });
+  });
+"#;
+
+// ノート / タグ / NoteTypeごとのページと検索インデックスを作る
+pub fn build_site(pages: &[SitePage]) -> Vec<SiteFile> {
+    let mut pages: Vec<&SitePage> = pages.iter().collect();
+    pages.sort_by_key(|p| p.title.to_lowercase());
+
+    let published: HashSet<&str> = pages.iter().map(|p| p.id.as_str()).collect();
+    let by_id: HashMap<&str, &SitePage> = pages.iter().map(|p| (p.id.as_str(), *p)).collect();
+
+    // 公開するノート同士のリンクだけでバックリンクを作る
+    let mut backlinks: HashMap<&str, Vec<&SitePage>> = HashMap::new();
+    for page in &pages {
+        for target in extract_note_links(&page.body) {
+            if let Some(target) = by_id.get(target.as_str())
+                && target.id != page.id
+            {
+                backlinks.entry(target.id.as_str()).or_default().push(page);
+            }
+        }
+    }
+
+    let mut tags: BTreeMap<&str, Vec<&SitePage>> = BTreeMap::new();
+    for page in &pages {
+        for tag in &page.tags {
+            tags.entry(tag.as_str()).or_default().push(page);
+        }
+    }
+    let slugs = tag_slugs(&tags);
+
+    let mut files = vec![];
+    let mut search = vec![];
+    for page in &pages {
+        // 非公開のノートへのリンクはテキストだけ残す
+        let body = strip_title_heading(page.body.trim(), &page.title);
+        let body = replace_note_links(body, |text, id| {
+            if published.contains(id) {
+                format!("[{}]({}.html)", text, id)
+            } else {
+                text.to_string()
+            }
+        });
+
+        let mut content = format!("<h1>{}</h1>\n", escape_html(&page.title));
+        content.push_str(&format!(
+            "<p class=\"meta\"><a href=\"../types/{}.html\">{}</a> · {}",
+            page.type_.name(),
+            page.type_,
+            page.created_at.format("%Y-%m-%d")
+        ));
+        for tag in &page.tags {
+            content.push_str(&format!(
+                " <a class=\"tag\" href=\"../tags/{}.html\">#{}</a>",
+                slugs[tag.as_str()],
+                escape_html(tag)
+            ));
+        }
+        content.push_str("</p>\n");
+        content.push_str(&markdown_to_html(&body));
+
+        content.push_str("<section class=\"backlinks\">\n<h2>Backlinks</h2>\n");
+        match backlinks.get(page.id.as_str()) {
+            Some(sources) => content.push_str(&note_list(sources, "")),
+            None => content.push_str("<p>No backlinks.</p>\n"),
+        }
+        content.push_str("</section>\n");
+
+        files.push(SiteFile {
+            path: PathBuf::from("notes").join(format!("{}.html", page.id)),
+            content: layout(&page.title, "../", &content),
+        });
+
+        search.push(SearchEntry {
+            id: &page.id,
+            title: &page.title,
+            type_: page.type_.to_string(),
+            tags: &page.tags,
+            url: format!("notes/{}.html", page.id),
+            text: plain_text(&body),
+        });
+    }
+
+    for (tag, tagged) in &tags {
+        let content = format!(
+            "<h1>#{}</h1>\n{}",
+            escape_html(tag),
+            note_list(tagged, "../notes/")
+        );
+        files.push(SiteFile {
+            path: PathBuf::from("tags").join(format!("{}.html", slugs[tag])),
+            content: layout(&format!("#{}", tag), "../", &content),
+        });
+    }
+    files.push(SiteFile {
+        path: PathBuf::from("tags").join("index.html"),
+        content: layout(
+            "Tags",
+            "../",
+            &format!("<h1>Tags</h1>\n{}", tag_list(&tags, &slugs, "")),
+        ),
+    });
+
+    // 組み込みの種類のあとに、設定で追加した種類のうちノートがあるものを並べる
+    let mut types: Vec<NoteType> = NoteType::ALL.to_vec();
+    let mut custom: Vec<NoteType> = pages
+        .iter()
+        .map(|p| p.type_.clone())
+        .filter(|t| !types.contains(t))
+        .collect();
+    custom.sort_by(|a, b| a.name().cmp(b.name()));
+    custom.dedup();
+    types.extend(custom);
+
+    for type_ in &types {
+        let typed: Vec<&SitePage> = pages
+            .iter()
+            .copied()
+            .filter(|p| &p.type_ == type_)
+            .collect();
+        let list = if typed.is_empty() {
+            "<p>No notes.</p>\n".to_string()
+        } else {
+            note_list(&typed, "../notes/")
+        };
+        files.push(SiteFile {
+            path: PathBuf::from("types").join(format!("{}.html", type_.name())),
+            content: layout(
+                &type_.to_string(),
+                "../",
+                &format!("<h1>{}</h1>\n{}", type_, list),
+            ),
+        });
+    }
+
+    let mut index = String::from(
+        "<h1>Zettelkasten</h1>\n<input id=\"search\" type=\"search\" placeholder=\"Search notes\">\n<ul id=\"results\"></ul>\n<h2>Note types</h2>\n<ul>\n",
+    );
+    for type_ in &types {
+        let count = pages.iter().filter(|p| &p.type_ == type_).count();
+        index.push_str(&format!(
+            "<li><a href=\"types/{}.html\">{}</a> ({})</li>\n",
+            type_.name(),
+            type_,
+            count
+        ));
+    }
+    index.push_str("</ul>\n<h2>Tags</h2>\n");
+    index.push_str(&tag_list(&tags, &slugs, "tags/"));
+    index.push_str("<h2>All notes</h2>\n");
+    index.push_str(&note_list(&pages, "notes/"));
+    index.push_str("<script src=\"search.js\"></script>\n");
+    files.push(SiteFile {
+        path: PathBuf::from("index.html"),
+        content: layout("Zettelkasten", "", &index),
+    });
+
+    files.push(SiteFile {
+        path: PathBuf::from("search-index.json"),
+        content: serde_json::to_string(&search).expect("search index is always serializable"),
+    });
+    files.push(SiteFile {
+        path: PathBuf::from("search.js"),
+        content: SEARCH_SCRIPT.to_string(),
+    });
+    files.push(SiteFile {
+        path: PathBuf::from("style.css"),
+        content: STYLE.to_string(),
+    });
+
+    files
+}
+
+pub fn markdown_to_html(markdown: &str) -> String {
+    let options = Options::ENABLE_TABLES
+        | Options::ENABLE_STRIKETHROUGH
+        | Options::ENABLE_TASKLISTS
+        | Options::ENABLE_FOOTNOTES;
+    // 本文中のHTMLはそのまま出さずにテキストとしてエスケープする
+    let events = Parser::new_ext(markdown, options).map(|event| match event {
+        Event::Html(html) | Event::InlineHtml(html) => Event::Text(html),
+        event => event,
+    });
+    let mut out = String::new();
+    html::push_html(&mut out, events);
+    out
+}
+
+// 検索用にMarkdownの記法を外した本文
+fn plain_text(markdown: &str) -> String {
+    let mut text = String::new();
+    for event in Parser::new(markdown) {
+        match event {
+            Event::Text(t) | Event::Code(t) => {
+                text.push_str(&t);
+                text.push(' ');
+            }
+            Event::SoftBreak | Event::HardBreak => text.push(' '),
+            _ => {}
+        }
+    }
+    text.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+fn layout(title: &str, root: &str, content: &str) -> String {
+    format!(
+        "<!DOCTYPE html>
+<html lang=\"en\">
+<head>
+<meta charset=\"utf-8\">
+<meta name=\"viewport\" content=\"width=device-width, initial-scale=1\">
+<title>{title}</title>
+<link rel=\"stylesheet\" href=\"{root}style.css\">
+</head>
+<body>
+<nav><a href=\"{root}index.html\">Home</a><a href=\"{root}tags/index.html\">Tags</a></nav>
+<main>
+{content}</main>
+</body>
+</html>
+",
+        title = escape_html(title),
+        root = root,
+        content = content
+    )
+}
+
+fn note_list(pages: &[&SitePage], prefix: &str) -> String {
+    let mut out = String::from("<ul>\n");
+    for page in pages {
+        out.push_str(&format!(
+            "<li><a href=\"{}{}.html\">{}</a></li>\n",
+            prefix,
+            page.id,
+            escape_html(&page.title)
+        ));
+    }
+    out.push_str("</ul>\n");
+    out
+}
+
+fn tag_list(
+    tags: &BTreeMap<&str, Vec<&SitePage>>,
+    slugs: &HashMap<&str, String>,
+    prefix: &str,
+) -> String {
+    if tags.is_empty() {
+        return "<p>No tags.</p>\n".to_string();
+    }
+    let mut out = String::from("<ul>\n");
+    for (tag, pages) in tags {
+        out.push_str(&format!(
+            "<li><a href=\"{}{}.html\">#{}</a> ({})</li>\n",
+            prefix,
+            slugs[tag],
+            escape_html(tag),
+            pages.len()
+        ));
+    }
+    out.push_str("</ul>\n");
+    out
+}
+
+// ファイル名に使えない文字は `-` にする
+fn slugify(name: &str) -> String {
+    name.to_lowercase()
+        .chars()
+        .map(|c| if c.is_alphanumeric() { c } else { '-' })
+        .collect()
+}
+
+// `c++` と `c--` のように同じslugになるtagには `-2`, `-3`... を付けて分ける
+fn tag_slugs<'a>(tags: &BTreeMap<&'a str, Vec<&SitePage>>) -> HashMap<&'a str, String> {
+    let mut used: HashSet<String> = HashSet::new();
+    let mut slugs = HashMap::new();
+    for tag in tags.keys() {
+        let base = slugify(tag);
+        let mut slug = base.clone();
+        let mut n = 2;
+        while !used.insert(slug.clone()) {
+            slug = format!("{}-{}", base, n);
+            n += 1;
+        }
+        slugs.insert(*tag, slug);
+    }
+    slugs
+}
+
+fn escape_html(s: &str) -> String {
+    s.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+        .replace('\'', "&#39;")
+}
+
+// Test
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn page(id: &str, title: &str, tags: &[&str], body: &str) -> SitePage {
+        SitePage {
+            id: id.into(),
+            title: title.into(),
+            type_: NoteType::Permanent,
+            tags: tags.iter().map(|t| t.to_string()).collect(),
+            created_at: NaiveDateTime::parse_from_str("2025-06-08 12:00:00", "%Y-%m-%d %H:%M:%S")
+                .unwrap(),
+            body: body.into(),
+        }
+    }
+
+    #[test]
+    fn test_build_site() {
+        let pages = vec![
+            page(
+                "20250608T120000",
+                "Ownership",
+                &["rust"],
+                "## Ownership\n\nSee [Borrowing](./20250608T120001.md) and [Secret](./20250608T129999.md).",
+            ),
+            page(
+                "20250608T120001",
+                "Borrowing",
+                &["rust"],
+                "Borrowing <rules>",
+            ),
+        ];
+
+        let files = build_site(&pages);
+        let file = |path: &str| {
+            files
+                .iter()
+                .find(|f| f.path.as_path() == std::path::Path::new(path))
+                .map(|f| f.content.as_str())
+                .unwrap()
+        };
+
+        let ownership = file("notes/20250608T120000.html");
+        assert!(ownership.contains("<a href=\"20250608T120001.html\">Borrowing</a>"));
+        assert!(ownership.contains("and Secret."));
+        assert!(!ownership.contains("<h2>Ownership</h2>"));
+
+        let borrowing = file("notes/20250608T120001.html");
+        assert!(borrowing.contains("<li><a href=\"20250608T120000.html\">Ownership</a></li>"));
+
+        assert!(file("tags/rust.html").contains("../notes/20250608T120001.html"));
+        assert!(file("types/permanent.html").contains("Ownership"));
+        assert!(file("search-index.json").contains("\"url\":\"notes/20250608T120000.html\""));
+    }
+
+    #[test]
+    fn test_tag_slugs_and_raw_html() {
+        let pages = vec![page(
+            "20250608T120000",
+            "Languages",
+            &["c++", "c--"],
+            "<script>alert(1)</script>\n\nUse <b>both</b>.",
+        )];
+
+        let files = build_site(&pages);
+        let paths: Vec<_> = files.iter().map(|f| f.path.display().to_string()).collect();
+        assert!(paths.contains(&"tags/c--.html".to_string()));
+        assert!(paths.contains(&"tags/c---2.html".to_string()));
+
+        let note = &files[0].content;
+        assert!(!note.contains("<script>"));
+        assert!(note.contains("&lt;b&gt;both&lt;/b&gt;"));
+    }
+}
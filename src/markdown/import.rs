@@ -0,0 +1,393 @@
+use chrono::{NaiveDate, NaiveDateTime};
+use regex::Regex;
+use serde_yaml::Value;
+use std::{
+    collections::HashMap,
+    fs,
+    path::{Path, PathBuf},
+    str::FromStr,
+};
+
+use crate::{AppConfig, NoteType, ZettariumError, split_front_matter};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ImportFormat {
+    Obsidian,
+    Logseq,
+    Plain,
+}
+
+impl FromStr for ImportFormat {
+    type Err = ZettariumError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "obsidian" | "o" => Ok(ImportFormat::Obsidian),
+            "logseq" | "l" => Ok(ImportFormat::Logseq),
+            "plain" | "p" => Ok(ImportFormat::Plain),
+            _ => Err(ZettariumError::InvalidImportFormat(format!(
+                "{} (expected obsidian, logseq or plain)",
+                s
+            ))),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct ImportedNote {
+    pub source: PathBuf,
+    pub title: String,
+    pub type_: NoteType,
+    pub tags: Vec<String>,
+    pub created_at: Option<NaiveDateTime>,
+    pub aliases: Vec<String>,
+    pub body: String,
+    pub warnings: Vec<String>,
+}
+
+impl ImportedNote {
+    // ファイル名（拡張子なし）。wikilinkの解決に使う
+    pub fn stem(&self) -> String {
+        self.source
+            .file_stem()
+            .map(|s| s.to_string_lossy().to_string())
+            .unwrap_or_default()
+    }
+}
+
+pub fn collect_markdown_files(
+    dir: &Path,
+    format: ImportFormat,
+) -> Result<Vec<PathBuf>, ZettariumError> {
+    let mut roots = vec![];
+    if format == ImportFormat::Logseq {
+        // Logseqはpages/とjournals/だけを対象にする
+        for sub in ["pages", "journals"] {
+            let path = dir.join(sub);
+            if path.is_dir() {
+                roots.push(path);
+            }
+        }
+    }
+    if roots.is_empty() {
+        roots.push(dir.to_path_buf());
+    }
+
+    let mut files = vec![];
+    for root in roots {
+        walk_dir(&root, &mut files)?;
+    }
+    files.sort();
+    Ok(files)
+}
+
+fn walk_dir(dir: &Path, files: &mut Vec<PathBuf>) -> Result<(), ZettariumError> {
+    let entries = fs::read_dir(dir).map_err(|e| ZettariumError::io(dir, e))?;
+
+    for entry in entries {
+        let path = entry.map_err(|e| ZettariumError::io(dir, e))?.path();
+        let name = path
+            .file_name()
+            .map(|n| n.to_string_lossy().to_string())
+            .unwrap_or_default();
+
+        // .obsidian / .trash / .git / logseq/bak などは対象外
+        if name.starts_with('.') || name == "logseq" {
+            continue;
+        }
+
+        if path.is_dir() {
+            walk_dir(&path, files)?;
+        } else if path.extension().is_some_and(|ext| ext == "md") {
+            files.push(path);
+        }
+    }
+    Ok(())
+}
+
+pub fn parse_imported_note(
+    path: &Path,
+    format: ImportFormat,
+    config: &AppConfig,
+) -> Result<ImportedNote, ZettariumError> {
+    let content = fs::read_to_string(path).map_err(|e| ZettariumError::io(path, e))?;
+
+    let mut note = ImportedNote {
+        source: path.to_path_buf(),
+        title: String::new(),
+        type_: NoteType::Permanent,
+        tags: vec![],
+        created_at: None,
+        aliases: vec![],
+        body: String::new(),
+        warnings: vec![],
+    };
+
+    let (properties, body) = match format {
+        ImportFormat::Logseq => split_logseq_properties(&content),
+        _ => split_yaml_front_matter(&content, &mut note.warnings),
+    };
+    note.body = body.trim_start_matches(['\n', '\r']).to_string();
+
+    if let Some(title) = get_str(&properties, &["title"]) {
+        note.title = title;
+    } else if format == ImportFormat::Plain {
+        note.title = first_heading(&note.body).unwrap_or_else(|| stem_title(path, format));
+    } else {
+        note.title = stem_title(path, format);
+    }
+
+    note.tags = get_list(&properties, &["tags", "tag"])
+        .into_iter()
+        .map(|t| t.trim_start_matches('#').to_string())
+        .filter(|t| !t.is_empty())
+        .collect();
+    note.aliases = get_list(&properties, &["aliases", "alias"]);
+
+    if let Some(type_) = get_str(&properties, &["type", "note_type"]) {
+        match config.parse_type(&type_) {
+            Ok(t) => note.type_ = t,
+            Err(e) => note.warnings.push(format!("{}, imported as Permanent", e)),
+        }
+    } else if is_logseq_journal(path, format) {
+        note.type_ = NoteType::Fleeting;
+    }
+
+    if let Some(date) = get_str(&properties, &["created", "created_at", "date"]) {
+        match parse_datetime(&date) {
+            Some(dt) => note.created_at = Some(dt),
+            None => note
+                .warnings
+                .push(format!("Unrecognized created date \"{}\"", date)),
+        }
+    } else if is_logseq_journal(path, format) {
+        note.created_at = NaiveDate::parse_from_str(&note.stem(), "%Y_%m_%d")
+            .ok()
+            .and_then(|d| d.and_hms_opt(0, 0, 0));
+    }
+
+    Ok(note)
+}
+
+fn split_yaml_front_matter(
+    content: &str,
+    warnings: &mut Vec<String>,
+) -> (HashMap<String, Value>, String) {
+    if !content.starts_with("---") {
+        return (HashMap::new(), content.to_string());
+    }
+
+    let Some((yaml, body)) = split_front_matter(content) else {
+        warnings.push("Unterminated front matter, treated as body".to_string());
+        return (HashMap::new(), content.to_string());
+    };
+
+    // 空のfront matter（`---\n---`）はnullになる
+    let properties = match serde_yaml::from_str::<Option<HashMap<String, Value>>>(yaml) {
+        Ok(map) => map.unwrap_or_default(),
+        Err(e) => {
+            warnings.push(format!("Invalid front matter ignored: {}", e));
+            HashMap::new()
+        }
+    };
+    (properties, body.to_string())
+}
+
+// Logseqのページ先頭にある `key:: value` 形式のプロパティを読む
+fn split_logseq_properties(content: &str) -> (HashMap<String, Value>, String) {
+    let re = Regex::new(r"^\s*(?:-\s+)?([A-Za-z0-9_-]+)::\s*(.*)$").unwrap();
+    let mut properties = HashMap::new();
+    let mut consumed = 0;
+
+    for line in content.split_inclusive('\n') {
+        let Some(caps) = re.captures(line.trim_end()) else {
+            break;
+        };
+        let key = caps[1].to_lowercase();
+        let value = caps[2].trim().to_string();
+        let value = if key == "tags" || key == "alias" || key == "aliases" {
+            Value::Sequence(
+                value
+                    .split(',')
+                    .map(|v| Value::String(strip_brackets(v.trim()).to_string()))
+                    .collect(),
+            )
+        } else {
+            Value::String(value)
+        };
+        properties.insert(key, value);
+        consumed += line.len();
+    }
+
+    (properties, content[consumed..].to_string())
+}
+
+fn strip_brackets(s: &str) -> &str {
+    s.trim_start_matches("[[").trim_end_matches("]]")
+}
+
+fn get_str(properties: &HashMap<String, Value>, keys: &[&str]) -> Option<String> {
+    keys.iter().find_map(|k| match properties.get(*k)? {
+        Value::String(s) if !s.trim().is_empty() => Some(s.trim().to_string()),
+        Value::Number(n) => Some(n.to_string()),
+        _ => None,
+    })
+}
+
+fn get_list(properties: &HashMap<String, Value>, keys: &[&str]) -> Vec<String> {
+    for k in keys {
+        match properties.get(*k) {
+            Some(Value::Sequence(seq)) => {
+                return seq
+                    .iter()
+                    .filter_map(|v| match v {
+                        Value::String(s) => Some(s.trim().to_string()),
+                        Value::Number(n) => Some(n.to_string()),
+                        _ => None,
+                    })
+                    .collect();
+            }
+            Some(Value::String(s)) => {
+                return s
+                    .split([',', ' '])
+                    .map(|t| t.trim().to_string())
+                    .filter(|t| !t.is_empty())
+                    .collect();
+            }
+            _ => {}
+        }
+    }
+    vec![]
+}
+
+fn first_heading(body: &str) -> Option<String> {
+    body.lines()
+        .find_map(|line| line.strip_prefix("# "))
+        .map(|h| h.trim().to_string())
+}
+
+fn stem_title(path: &Path, format: ImportFormat) -> String {
+    let stem = path
+        .file_stem()
+        .map(|s| s.to_string_lossy().to_string())
+        .unwrap_or_default();
+    match format {
+        // Logseqは名前空間を `___` や `%2F` でファイル名に埋め込む
+        ImportFormat::Logseq => stem.replace("___", "/").replace("%2F", "/"),
+        _ => stem,
+    }
+}
+
+fn is_logseq_journal(path: &Path, format: ImportFormat) -> bool {
+    format == ImportFormat::Logseq
+        && path
+            .parent()
+            .and_then(|p| p.file_name())
+            .is_some_and(|n| n == "journals")
+}
+
+fn parse_datetime(s: &str) -> Option<NaiveDateTime> {
+    let s = s.trim().trim_matches(['[', ']']);
+    for fmt in [
+        "%Y-%m-%dT%H:%M:%S",
+        "%Y-%m-%d %H:%M:%S",
+        "%Y-%m-%dT%H:%M",
+        "%Y-%m-%d %H:%M",
+    ] {
+        if let Ok(dt) = NaiveDateTime::parse_from_str(s, fmt) {
+            return Some(dt);
+        }
+    }
+    for fmt in ["%Y-%m-%d", "%Y/%m/%d", "%Y_%m_%d", "%b %d, %Y"] {
+        if let Ok(d) = NaiveDate::parse_from_str(s, fmt) {
+            return d.and_hms_opt(0, 0, 0);
+        }
+    }
+    // タイムゾーン付きの場合はローカル時刻部分だけを使う
+    chrono::DateTime::parse_from_rfc3339(s)
+        .ok()
+        .map(|dt| dt.naive_local())
+}
+
+// `[[Name]]` / `[[Name|alias]]` / `[[Name#heading]]` をzettariumのリンクに書き換える
+// 解決できなかったリンクはそのまま残し、リンク先の名前を返す
+pub fn rewrite_wikilinks(
+    body: &str,
+    resolve: impl Fn(&str) -> Option<String>,
+) -> (String, Vec<String>) {
+    let re = Regex::new(r"(!?)\[\[([^\[\]|#]*)(#[^\[\]|]*)?(?:\|([^\[\]]*))?\]\]").unwrap();
+    let mut unresolved = vec![];
+
+    let rewritten = re.replace_all(body, |caps: &regex::Captures| {
+        let whole = caps[0].to_string();
+        let target = caps[2].trim();
+        // 埋め込み（![[image.png]]）は対象外
+        if !caps[1].is_empty() || target.is_empty() {
+            unresolved.push(whole.clone());
+            return whole;
+        }
+
+        match resolve(target) {
+            Some(id) => {
+                let label = caps
+                    .get(4)
+                    .map(|m| m.as_str().trim().to_string())
+                    .unwrap_or_else(|| {
+                        format!("{}{}", target, caps.get(3).map_or("", |m| m.as_str()))
+                    });
+                format!("[{}](./{}.md)", label, id)
+            }
+            None => {
+                unresolved.push(target.to_string());
+                whole
+            }
+        }
+    });
+
+    (rewritten.into_owned(), unresolved)
+}
+
+// Test
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_rewrite_wikilinks() {
+        let body =
+            "See [[Ownership]], [[borrowing|the borrow rules]] and [[Missing]]. ![[img.png]]";
+        let (rewritten, unresolved) =
+            rewrite_wikilinks(body, |name| match name.to_lowercase().as_str() {
+                "ownership" => Some("20250608T120000".into()),
+                "borrowing" => Some("20250608T120001".into()),
+                _ => None,
+            });
+
+        assert!(rewritten.contains("[Ownership](./20250608T120000.md)"));
+        assert!(rewritten.contains("[the borrow rules](./20250608T120001.md)"));
+        assert!(rewritten.contains("[[Missing]]"));
+        assert!(rewritten.contains("![[img.png]]"));
+        assert_eq!(
+            unresolved,
+            vec!["Missing".to_string(), "![[img.png]]".to_string()]
+        );
+    }
+
+    #[test]
+    fn test_logseq_properties() {
+        let content =
+            "title:: Async Rust\ntags:: [[rust]], async\ntype:: literature\n\n- first block\n";
+        let (props, body) = split_logseq_properties(content);
+        assert_eq!(get_str(&props, &["title"]).as_deref(), Some("Async Rust"));
+        assert_eq!(get_list(&props, &["tags"]), vec!["rust", "async"]);
+        assert!(body.contains("- first block"));
+    }
+
+    #[test]
+    fn test_parse_datetime_formats() {
+        assert!(parse_datetime("2025-06-08").is_some());
+        assert!(parse_datetime("2025-06-08 12:30").is_some());
+        assert!(parse_datetime("2025-06-08T12:30:00+09:00").is_some());
+        assert!(parse_datetime("yesterday").is_none());
+    }
+}
@@ -0,0 +1,119 @@
+use chrono::{Datelike, Duration, NaiveDate, NaiveTime};
+
+use crate::{LinkFormat, Zettel};
+
+// `z daily` の組み込みテンプレート（{{yesterday}} / {{tomorrow}} は前後の日のノートへのリンク）
+pub const DAILY_TEMPLATE: &str = "## {{title}}\n\n← {{yesterday}} | {{tomorrow}} →\n\n- \n";
+
+// 日付ごとのノートは `<日付>T000000` に固定して、前後の日から常に同じIDでリンクできるようにする
+pub fn journal_id(date: NaiveDate) -> String {
+    date.and_time(NaiveTime::MIN)
+        .format("%Y%m%dT%H%M%S")
+        .to_string()
+}
+
+pub fn journal_title(date: NaiveDate) -> String {
+    date.format("%Y-%m-%d").to_string()
+}
+
+pub fn journal_link(date: NaiveDate) -> String {
+    LinkFormat::Markdown.format(&journal_id(date), &journal_title(date))
+}
+
+// dateを含む週（月曜〜日曜）
+pub fn week_range(date: NaiveDate) -> (NaiveDate, NaiveDate) {
+    let monday = date - Duration::days(date.weekday().num_days_from_monday() as i64);
+    (monday, monday + Duration::days(6))
+}
+
+// 週に作成したノートを日ごとにまとめたMarkdownを返す
+pub fn render_weekly(date: NaiveDate, zettels: &[Zettel]) -> String {
+    let (monday, sunday) = week_range(date);
+    let week = monday.iso_week();
+    let mut out = format!(
+        "## Week {}-W{:02} ({} – {})\n",
+        week.year(),
+        week.week(),
+        journal_title(monday),
+        journal_title(sunday)
+    );
+
+    let mut zettels: Vec<&Zettel> = zettels
+        .iter()
+        .filter(|z| (monday..=sunday).contains(&z.created_at.date()))
+        .collect();
+    zettels.sort_by(|a, b| a.created_at.cmp(&b.created_at).then(a.id.cmp(&b.id)));
+
+    for day in monday.iter_days().take(7) {
+        let notes: Vec<&&Zettel> = zettels
+            .iter()
+            .filter(|z| z.created_at.date() == day)
+            .collect();
+        if notes.is_empty() {
+            continue;
+        }
+        out.push_str(&format!("\n### {}\n\n", day.format("%Y-%m-%d %a")));
+        for z in notes {
+            out.push_str(&format!(
+                "- {} ({})\n",
+                LinkFormat::Markdown.format(&z.id, &z.title),
+                z.type_
+            ));
+        }
+    }
+
+    if zettels.is_empty() {
+        out.push_str("\nNo notes were created this week.\n");
+    }
+    out
+}
+
+// Test
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::NoteType;
+    use chrono::NaiveDateTime;
+
+    fn zettel(id: &str, title: &str, type_: NoteType) -> Zettel {
+        let created = NaiveDateTime::parse_from_str(id, "%Y%m%dT%H%M%S").unwrap();
+        Zettel {
+            id: id.into(),
+            title: title.into(),
+            type_,
+            created_at: created,
+            updated_at: created,
+            archived: false,
+            deleted_at: None,
+            processed_at: None,
+        }
+    }
+
+    #[test]
+    fn test_render_weekly() {
+        let date = NaiveDate::from_ymd_opt(2025, 6, 12).unwrap();
+        assert_eq!(journal_id(date), "20250612T000000");
+        assert_eq!(
+            week_range(date),
+            (
+                NaiveDate::from_ymd_opt(2025, 6, 9).unwrap(),
+                NaiveDate::from_ymd_opt(2025, 6, 15).unwrap()
+            )
+        );
+
+        let zettels = vec![
+            zettel("20250612T093000", "Standup", NoteType::Fleeting),
+            zettel("20250609T000000", "2025-06-09", NoteType::Fleeting),
+            zettel("20250608T235959", "Last week", NoteType::Permanent),
+            zettel("20250615T120000", "Ownership", NoteType::Permanent),
+        ];
+
+        assert_eq!(
+            render_weekly(date, &zettels),
+            "## Week 2025-W24 (2025-06-09 – 2025-06-15)\n\
+             \n### 2025-06-09 Mon\n\n- [2025-06-09](./20250609T000000.md) (Fleeting)\n\
+             \n### 2025-06-12 Thu\n\n- [Standup](./20250612T093000.md) (Fleeting)\n\
+             \n### 2025-06-15 Sun\n\n- [Ownership](./20250615T120000.md) (Permanent)\n"
+        );
+    }
+}
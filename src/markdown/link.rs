@@ -0,0 +1,173 @@
+use regex::Regex;
+use serde::Deserialize;
+use std::{ops::Range, str::FromStr};
+
+use crate::ZettariumError;
+
+// `z find --link` などで出力するノートへのリンクの書式
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Deserialize)]
+#[serde(try_from = "String")]
+pub enum LinkFormat {
+    // [title](./<id>.md)
+    #[default]
+    Markdown,
+    // [[<id>|title]]
+    Wikilink,
+    // [[file:<id>.md][title]]
+    Org,
+    // <id>
+    Id,
+}
+
+impl LinkFormat {
+    pub fn format(&self, id: &str, title: &str) -> String {
+        match self {
+            LinkFormat::Markdown => format!("[{}](./{}.md)", title, id),
+            LinkFormat::Wikilink => format!("[[{}|{}]]", id, title),
+            LinkFormat::Org => format!("[[file:{}.md][{}]]", id, title),
+            LinkFormat::Id => id.to_string(),
+        }
+    }
+}
+
+// 本文中のノート間リンク（どの書式でも）
+struct NoteLink<'a> {
+    range: Range<usize>,
+    text: &'a str,
+    id: &'a str,
+}
+
+// LinkFormat の書式ごとのパターン（Markdown / Wikilink / Org / IDのみ の順に試す）
+fn note_link_regex() -> Regex {
+    Regex::new(concat!(
+        r"\[([^\]]*)\]\((?:\./)?(\d{8}T\d{6})\.md\)",
+        r"|\[\[(\d{8}T\d{6})(?:\|([^\]]*))?\]\]",
+        r"|\[\[file:(\d{8}T\d{6})\.md\](?:\[([^\]]*)\])?\]",
+        r"|\b(\d{8}T\d{6})\b",
+    ))
+    .unwrap()
+}
+
+fn find_note_links(body: &str) -> Vec<NoteLink<'_>> {
+    let mut links = vec![];
+    for caps in note_link_regex().captures_iter(body) {
+        let whole = caps.get(0).unwrap();
+        let (id, text) = if let Some(id) = caps.get(2) {
+            (id, caps.get(1))
+        } else if let Some(id) = caps.get(3) {
+            (id, caps.get(4))
+        } else if let Some(id) = caps.get(5) {
+            (id, caps.get(6))
+        } else {
+            // `assets/<id>/...` のようなパスの一部はリンクとみなさない
+            let before = body[..whole.start()].chars().next_back();
+            let after = body[whole.end()..].chars().next();
+            if before == Some('/') || after == Some('/') {
+                continue;
+            }
+            (whole, None)
+        };
+        links.push(NoteLink {
+            range: whole.range(),
+            text: text.map_or(id.as_str(), |t| t.as_str()),
+            id: id.as_str(),
+        });
+    }
+    links
+}
+
+// ノート間リンクのリンク先IDを本文中の順に返す（重複は除く）
+pub fn extract_note_links(body: &str) -> Vec<String> {
+    let mut ids: Vec<String> = vec![];
+    for link in find_note_links(body) {
+        if !ids.iter().any(|id| id == link.id) {
+            ids.push(link.id.to_string());
+        }
+    }
+    ids
+}
+
+// ノート間リンクを `f(リンクテキスト, ID)` の結果に置き換える
+pub fn replace_note_links(body: &str, mut f: impl FnMut(&str, &str) -> String) -> String {
+    let mut out = String::with_capacity(body.len());
+    let mut last = 0;
+    for link in find_note_links(body) {
+        out.push_str(&body[last..link.range.start]);
+        out.push_str(&f(link.text, link.id));
+        last = link.range.end;
+    }
+    out.push_str(&body[last..]);
+    out
+}
+
+impl FromStr for LinkFormat {
+    type Err = ZettariumError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "markdown" | "md" => Ok(LinkFormat::Markdown),
+            "wikilink" | "wiki" => Ok(LinkFormat::Wikilink),
+            "org" => Ok(LinkFormat::Org),
+            "id" => Ok(LinkFormat::Id),
+            _ => Err(ZettariumError::InvalidLinkFormat(format!(
+                "{} (expected markdown, wikilink, org or id)",
+                s
+            ))),
+        }
+    }
+}
+
+impl TryFrom<String> for LinkFormat {
+    type Error = ZettariumError;
+
+    fn try_from(s: String) -> Result<Self, Self::Error> {
+        s.parse()
+    }
+}
+
+// Test
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_link_format() {
+        let id = "20250601T120000";
+        let title = "Notes - a how-to";
+
+        assert_eq!(
+            LinkFormat::Markdown.format(id, title),
+            "[Notes - a how-to](./20250601T120000.md)"
+        );
+        assert_eq!(
+            "wiki".parse::<LinkFormat>().unwrap().format(id, title),
+            "[[20250601T120000|Notes - a how-to]]"
+        );
+        assert_eq!(
+            "org".parse::<LinkFormat>().unwrap().format(id, title),
+            "[[file:20250601T120000.md][Notes - a how-to]]"
+        );
+        assert_eq!(LinkFormat::Id.format(id, title), id);
+        assert!("html".parse::<LinkFormat>().is_err());
+
+        // どの書式で書いたリンクも同じように読む
+        let body = "[[20250601T120000|Ownership]], [[20250601T120001]], \
+                    [[file:20250601T120002.md][Borrowing]], 20250601T120003 and \
+                    [Ownership](./20250601T120000.md) ![img](./assets/20250601T120004/a.png)";
+        assert_eq!(
+            extract_note_links(body),
+            vec![
+                "20250601T120000",
+                "20250601T120001",
+                "20250601T120002",
+                "20250601T120003"
+            ]
+        );
+        assert_eq!(
+            replace_note_links(body, |text, id| format!("<{}:{}>", text, id)),
+            "<Ownership:20250601T120000>, <20250601T120001:20250601T120001>, \
+             <Borrowing:20250601T120002>, <20250601T120003:20250601T120003> and \
+             <Ownership:20250601T120000> ![img](./assets/20250601T120004/a.png)"
+        );
+    }
+}
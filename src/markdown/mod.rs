@@ -1,5 +1,21 @@
+pub mod bibliography;
+pub mod compile;
+pub mod export;
+pub mod html;
+pub mod import;
+pub mod journal;
+pub mod link;
 pub mod parser;
+pub mod template;
 pub mod writer;
 
+pub use bibliography::*;
+pub use compile::*;
+pub use export::*;
+pub use html::*;
+pub use import::*;
+pub use journal::*;
+pub use link::*;
 pub use parser::*;
+pub use template::*;
 pub use writer::*;
@@ -1,21 +1,170 @@
-use anyhow::Error;
-use std::{fs, path::PathBuf};
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
 
-use crate::Zettel;
+use crate::{Diagnostic, FrontMatter, ParsedMarkdown, ZettariumError, Zettel};
 
-pub fn parse_markdown(zettel: &Zettel, dir: PathBuf) -> Result<(String, String), Error> {
-    let path = format!("{}/{}.md", dir.display(), zettel.id);
+pub fn parse_markdown(zettel: &Zettel, dir: PathBuf) -> Result<ParsedMarkdown, ZettariumError> {
+    let path = dir.join(format!("{}.md", zettel.id));
+    parse_markdown_file(&path)
+}
+
+pub fn parse_markdown_file(path: &Path) -> Result<ParsedMarkdown, ZettariumError> {
+    let content = fs::read_to_string(path).map_err(|e| ZettariumError::io(path, e))?;
+    let mut parsed = parse_markdown_str(&content, path)?;
+
+    // ファイル名とfront matterのidが食い違っている場合は知らせる
+    if let (Some(front_matter), Some(stem)) = (&parsed.front_matter, path.file_stem())
+        && front_matter.zettel.id != stem.to_string_lossy()
+    {
+        parsed.diagnostics.push(Diagnostic {
+            line: 2,
+            message: format!(
+                "id \"{}\" does not match the file name",
+                front_matter.zettel.id
+            ),
+        });
+    }
+    Ok(parsed)
+}
+
+pub fn parse_markdown_str(content: &str, path: &Path) -> Result<ParsedMarkdown, ZettariumError> {
+    let content = content.trim_start_matches('\u{feff}');
+    let invalid = |line: usize, message: String| ZettariumError::FrontMatterInvalid {
+        path: path.to_path_buf(),
+        line,
+        message,
+    };
+
+    let Some((yaml, body)) = split_front_matter(content) else {
+        if content
+            .lines()
+            .next()
+            .is_some_and(|l| l.trim_end() == "---")
+        {
+            return Err(invalid(1, "front matter is not closed with `---`".into()));
+        }
+        return Ok(ParsedMarkdown {
+            front_matter: None,
+            body: content.to_string(),
+            diagnostics: vec![Diagnostic {
+                line: 1,
+                message: "no front matter".into(),
+            }],
+        });
+    };
+
+    // 1行目の `---` の分だけ行番号をずらす
+    let front_matter = serde_yaml::from_str::<FrontMatter>(yaml).map_err(|e| {
+        let line = e.location().map_or(1, |l| l.line() + 1);
+        invalid(line, e.to_string())
+    })?;
+
+    if front_matter.zettel.title.trim().is_empty() {
+        let line = 1 + yaml_line_of(yaml, "title:").unwrap_or(1);
+        return Err(invalid(line, "title must not be empty".into()));
+    }
+
+    Ok(ParsedMarkdown {
+        front_matter: Some(front_matter),
+        body: body.to_string(),
+        diagnostics: vec![],
+    })
+}
+
+// 行頭の `---` だけを区切りとして扱い、(front matter, body) に分割する
+// front matterがない、または閉じられていない場合は None
+pub fn split_front_matter(content: &str) -> Option<(&str, &str)> {
+    let mut lines = content.split_inclusive('\n');
+    let first = lines.next()?;
+    if first.trim_end() != "---" {
+        return None;
+    }
+
+    let start = first.len();
+    let mut offset = start;
+    for line in lines {
+        if line.trim_end() == "---" {
+            return Some((&content[start..offset], &content[offset + line.len()..]));
+        }
+        offset += line.len();
+    }
+    None
+}
+
+// front matterが必須の場面（reindex / エディタ編集後）で使う
+pub fn read_front_matter(path: &Path) -> Result<(FrontMatter, String), ZettariumError> {
+    let parsed = parse_markdown_file(path)?;
+    match parsed.front_matter {
+        Some(front_matter) => Ok((front_matter, parsed.body)),
+        None => Err(ZettariumError::FrontMatterInvalid {
+            path: path.to_path_buf(),
+            line: 1,
+            message: "no front matter".into(),
+        }),
+    }
+}
+
+fn yaml_line_of(yaml: &str, prefix: &str) -> Option<usize> {
+    yaml.lines()
+        .position(|l| l.starts_with(prefix))
+        .map(|i| i + 1)
+}
+
+// Test
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const FRONT_MATTER: &str = "---
+id: 20250608T120000
+title: Ownership
+type_: Permanent
+created_at: 2025-06-08T12:00:00
+updated_at: 2025-06-08T12:00:00
+archived: false
+tags:
+- rust
+---
+";
+
+    #[test]
+    fn test_parse_body_with_horizontal_rule() {
+        let content = format!("{}\n## Ownership\n\n---\n\nafter the rule\n", FRONT_MATTER);
+        let parsed = parse_markdown_str(&content, Path::new("20250608T120000.md")).unwrap();
 
-    let content = fs::read_to_string(path)?;
-    let re = String::from("---");
+        let front_matter = parsed.front_matter.unwrap();
+        assert_eq!(front_matter.zettel.title, "Ownership");
+        assert_eq!(front_matter.tags, vec!["rust".to_string()]);
+        assert!(parsed.body.contains("---\n\nafter the rule"));
+        assert!(parsed.diagnostics.is_empty());
+    }
 
-    let splitted_contents = content.splitn(3, &re);
+    #[test]
+    fn test_parse_without_front_matter() {
+        let parsed = parse_markdown_str("# Just a note\n", Path::new("x.md")).unwrap();
+        assert!(parsed.front_matter.is_none());
+        assert_eq!(parsed.body, "# Just a note\n");
+        assert_eq!(parsed.diagnostics.len(), 1);
+    }
 
-    let contents: Vec<_> = splitted_contents
-        .into_iter()
-        .map(|content| content.to_string())
-        .collect();
+    #[test]
+    fn test_parse_errors_have_line_numbers() {
+        let unterminated = "---\nid: x\n\nbody";
+        match parse_markdown_str(unterminated, Path::new("x.md")) {
+            Err(ZettariumError::FrontMatterInvalid { line, .. }) => assert_eq!(line, 1),
+            other => panic!("unexpected result: {:?}", other),
+        }
 
-    let markdown_content = (contents[1].clone(), contents[2].clone());
-    Ok(markdown_content)
+        // 設定から消した種類は読めるが、種類の名前として使えない値はエラー
+        let invalid = FRONT_MATTER.replace("type_: Permanent", "type_: not a type");
+        match parse_markdown_str(&invalid, Path::new("x.md")) {
+            Err(ZettariumError::FrontMatterInvalid { line, message, .. }) => {
+                assert!(line > 1);
+                assert!(message.contains("Invalid note type"));
+            }
+            other => panic!("unexpected result: {:?}", other),
+        }
+    }
 }
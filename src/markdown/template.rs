@@ -0,0 +1,152 @@
+use regex::{Captures, Regex};
+use std::{collections::HashMap, fs, path::PathBuf};
+
+use crate::{AppConfig, DAILY_TEMPLATE, NoteType, ZettariumError};
+
+pub const DEFAULT_TEMPLATE: &str = "## {{title}}\n";
+pub const DAILY_TEMPLATE_FILE: &str = "daily.md";
+
+// `z init` でテンプレートディレクトリに書き出す初期テンプレート
+pub fn default_template(type_: &NoteType) -> &'static str {
+    match type_ {
+        NoteType::Literature => {
+            "## {{title}}\n\n### Source\n\n\n### Quote\n\n> \n\n### Thoughts\n\n"
+        }
+        NoteType::Structure => "## {{title}}\n\n### Outline\n\n- \n",
+        NoteType::Index => "## {{title}}\n\n- \n",
+        NoteType::Fleeting | NoteType::Permanent | NoteType::Custom(_) => DEFAULT_TEMPLATE,
+    }
+}
+
+pub fn template_file_name(type_: &NoteType) -> String {
+    format!("{}.md", type_.name())
+}
+
+// 名前付きテンプレート → 種類の設定のテンプレート → NoteTypeごとのテンプレート → 組み込みの順で探す
+pub fn load_template(
+    config: &AppConfig,
+    name: Option<&str>,
+    type_: &NoteType,
+) -> Result<String, ZettariumError> {
+    let dir = PathBuf::from(&config.templates.dir);
+    let name = name.or_else(|| {
+        config
+            .type_config(type_)
+            .and_then(|c| c.template.as_deref())
+    });
+
+    if let Some(name) = name {
+        let file = if name.ends_with(".md") {
+            name.to_string()
+        } else {
+            format!("{}.md", name)
+        };
+        let path = dir.join(file);
+        if !path.exists() {
+            return Err(ZettariumError::TemplateNotFound(name.to_string()));
+        }
+        return fs::read_to_string(&path).map_err(|e| ZettariumError::io(&path, e));
+    }
+
+    let path = dir.join(template_file_name(type_));
+    if path.exists() {
+        return fs::read_to_string(&path).map_err(|e| ZettariumError::io(&path, e));
+    }
+    Ok(DEFAULT_TEMPLATE.to_string())
+}
+
+// `[journal] template` → daily.md → 組み込みの順で探す
+pub fn load_daily_template(config: &AppConfig) -> Result<String, ZettariumError> {
+    if let Some(name) = &config.journal.template {
+        return load_template(config, Some(name), &NoteType::Fleeting);
+    }
+    let path = PathBuf::from(&config.templates.dir).join(DAILY_TEMPLATE_FILE);
+    if path.exists() {
+        return fs::read_to_string(&path).map_err(|e| ZettariumError::io(&path, e));
+    }
+    Ok(DAILY_TEMPLATE.to_string())
+}
+
+pub fn template_uses(template: &str, variable: &str) -> bool {
+    variable_regex()
+        .captures_iter(template)
+        .any(|caps| &caps[1] == variable)
+}
+
+// `{{name}}` を置き換える。未知の変数はそのまま残す
+pub fn render_template(template: &str, variables: &HashMap<&str, String>) -> String {
+    variable_regex()
+        .replace_all(template, |caps: &Captures| match variables.get(&caps[1]) {
+            Some(value) => value.clone(),
+            None => caps[0].to_string(),
+        })
+        .into_owned()
+}
+
+// 描画済みのテンプレートの見出しごとの節のうち、本文にない見出しのものを返す
+// タイトルの見出しと最初の見出しより前は対象にしない
+pub fn missing_template_sections(rendered: &str, body: &str, title: &str) -> Vec<String> {
+    let heading_text = |line: &str| line.trim_start_matches('#').trim().to_lowercase();
+    let existing: Vec<String> = body
+        .lines()
+        .filter(|line| line.starts_with('#'))
+        .map(heading_text)
+        .collect();
+
+    let mut sections: Vec<(String, String)> = vec![];
+    for line in rendered.lines() {
+        if line.starts_with('#') {
+            sections.push((heading_text(line), String::new()));
+        }
+        if let Some((_, section)) = sections.last_mut() {
+            section.push_str(line);
+            section.push('\n');
+        }
+    }
+
+    sections
+        .into_iter()
+        .filter(|(heading, _)| heading != &title.to_lowercase() && !existing.contains(heading))
+        .map(|(_, section)| section)
+        .collect()
+}
+
+fn variable_regex() -> Regex {
+    Regex::new(r"\{\{\s*([A-Za-z_]+)\s*\}\}").unwrap()
+}
+
+// Test
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_render_template() {
+        let template = "# {{title}}\n{{ id }} / {{date}}\n{{tags}}\n{{unknown}}";
+        let variables = HashMap::from([
+            ("title", "Ownership".to_string()),
+            ("id", "20250608T120000".to_string()),
+            ("date", "2025-06-08".to_string()),
+            ("tags", "#rust #concept".to_string()),
+        ]);
+
+        let rendered = render_template(template, &variables);
+        assert_eq!(
+            rendered,
+            "# Ownership\n20250608T120000 / 2025-06-08\n#rust #concept\n{{unknown}}"
+        );
+        assert!(template_uses(template, "id"));
+        assert!(!template_uses(template, "clipboard"));
+    }
+
+    #[test]
+    fn test_missing_template_sections() {
+        let rendered = "## Paper\n\n### Source\n\n\n### Quote\n\n> \n\n### Thoughts\n\n";
+        let body = "## Paper\n\nSome notes.\n\n### Thoughts\n\nGood read.\n";
+
+        assert_eq!(
+            missing_template_sections(rendered, body, "Paper"),
+            vec!["### Source\n\n\n", "### Quote\n\n> \n\n"]
+        );
+    }
+}
@@ -1,64 +1,256 @@
 use crate::model::Markdown;
 use crate::{
-    AppConfig, Body, FrontMatter, Zettel, ensure_zettel_exists, parse_markdown,
-    update_zettel_timestamp_only,
+    AppConfig, Body, FrontMatter, NewSource, SourceMeta, ZettariumError, Zettel, confirm,
+    create_revision, dedup_and_warn, delete_sources_by_zettel_id, delete_zettel_tag,
+    ensure_type_rules, ensure_zettel_exists, get_latest_revision, get_source_by_zettel_id,
+    get_tag_by_zettel_id, parse_markdown, read_front_matter, split_front_matter, update_zettel,
+    update_zettel_timestamp_only, upsert_source,
 };
-use anyhow::Result;
-use diesel::SqliteConnection;
+use diesel::{Connection, SqliteConnection};
 use std::fs::File;
 use std::io::{BufWriter, Write};
+use std::path::Path;
 use std::{fs, path::PathBuf};
 
-pub fn write_to_markdown(markdown: &Markdown, dir: PathBuf) -> Result<()> {
-    fs::create_dir_all(&dir)?;
+pub fn write_to_markdown(
+    conn: &mut SqliteConnection,
+    markdown: &Markdown,
+    dir: PathBuf,
+) -> Result<(), ZettariumError> {
+    let path = save_markdown(conn, markdown, dir)?;
+    eprintln!("Markdown saved to {}", path.display());
+    Ok(())
+}
+
+// 書き出したファイルのパスを返す（表示はしない。TUIからも使う）
+pub fn save_markdown(
+    conn: &mut SqliteConnection,
+    markdown: &Markdown,
+    dir: PathBuf,
+) -> Result<PathBuf, ZettariumError> {
+    fs::create_dir_all(&dir).map_err(|e| ZettariumError::io(&dir, e))?;
 
-    let filename = format!("{}.md", markdown.get_front_matter().get_zettel().id);
+    let id = &markdown.get_front_matter().get_zettel().id;
+    let filename = format!("{}.md", id);
     let path = dir.join(filename);
 
-    let file = File::create(&path)?;
+    // 履歴のないノート（履歴の機能より前に作ったもの）は上書き前の内容を最初のリビジョンにする
+    let latest = get_latest_revision(conn, id)?;
+    if path.exists() && latest.is_none() {
+        let content = fs::read_to_string(&path).map_err(|e| ZettariumError::io(&path, e))?;
+        if let Some((front_matter, body)) = split_front_matter(&content) {
+            let body = body.trim_start_matches(['\r', '\n']);
+            let body = body.strip_suffix('\n').unwrap_or(body);
+            create_revision(conn, id, front_matter, body)?;
+        }
+    }
+
+    let file = File::create(&path).map_err(|e| ZettariumError::io(&path, e))?;
     let mut writer = BufWriter::new(file);
-    write!(writer, "{}", markdown)?;
+    write!(writer, "{}", markdown).map_err(|e| ZettariumError::io(&path, e))?;
+    writer.flush().map_err(|e| ZettariumError::io(&path, e))?;
 
-    println!("Markdown saved to {}", path.display());
-    Ok(())
+    // 上書きした内容を履歴として残す（`z history` / `z revert`）
+    if latest.is_some_and(|l| l.same_content(markdown.get_front_matter(), &markdown.get_body().0)) {
+        return Ok(path);
+    }
+    let front_matter = serde_yaml::to_string(markdown.get_front_matter()).map_err(|e| {
+        ZettariumError::FrontMatterInvalid {
+            path: path.clone(),
+            line: 1,
+            message: e.to_string(),
+        }
+    })?;
+    create_revision(conn, id, &front_matter, &markdown.get_body().0)?;
+
+    Ok(path)
 }
 
 pub fn edit_with_editor(
     conn: &mut SqliteConnection,
     id: &str,
     config: &AppConfig,
-) -> Result<Zettel> {
+) -> Result<Zettel, ZettariumError> {
     let zettel = ensure_zettel_exists(conn, id)?;
     let path = PathBuf::from(format!("{}/{}.md", &config.paths.zettel_dir, zettel.id));
+    if !path.exists() {
+        return Err(ZettariumError::FileMissing(path));
+    }
 
     let editor = &config.editor.editor;
-    let status = std::process::Command::new(editor).arg(&path).status()?;
+    loop {
+        let status = std::process::Command::new(editor)
+            .arg(&path)
+            .status()
+            .map_err(|e| {
+                ZettariumError::EditorFailed(format!("could not start {}: {}", editor, e))
+            })?;
+
+        if !status.success() {
+            println!("Edit was cancelled.");
+            return Err(ZettariumError::EditorFailed(format!(
+                "{} exited with {}",
+                editor, status
+            )));
+        }
+
+        // エディタで変更されたfront matterをDBに反映する
+        match read_edited_front_matter(&path, &zettel, config) {
+            Ok(front_matter) => return apply_front_matter(conn, &zettel, &front_matter),
+            Err(e) => {
+                eprintln!("Error: {}", e);
+                if !confirm("Reopen the editor to fix it?", true)? {
+                    println!("Front matter is invalid. Changes were not applied to the database.");
+                    return Err(e);
+                }
+            }
+        }
+    }
+}
+
+fn read_edited_front_matter(
+    path: &Path,
+    zettel: &Zettel,
+    config: &AppConfig,
+) -> Result<FrontMatter, ZettariumError> {
+    let (mut front_matter, body) = read_front_matter(path)?;
 
-    if !status.success() {
-        println!("Edit was cancelled.");
-        anyhow::bail!("Editor exited with non-zero status");
+    // id / created_at / archived はエディタからは変更させない
+    if front_matter.zettel.id != zettel.id {
+        println!(
+            "Warning: id cannot be changed in the editor, keeping {}.",
+            zettel.id
+        );
     }
 
-    // `updated_at` だけ更新する
-    let zettel = update_zettel_timestamp_only(conn, id)?; // ← 新たに関数を用意
+    let tags = front_matter
+        .tags
+        .iter()
+        .map(|t| t.trim().to_string())
+        .filter(|t| !t.is_empty())
+        .collect();
+    front_matter.tags = dedup_and_warn(tags);
+    front_matter.zettel.title = front_matter.zettel.title.trim().to_string();
 
-    Ok(zettel)
+    // 種類を変えた場合は設定にある種類か（略称も含めて）とルールを確かめる
+    if front_matter.zettel.type_ != zettel.type_ {
+        front_matter.zettel.type_ = config.parse_type(front_matter.zettel.type_.name())?;
+    }
+    let type_ = &front_matter.zettel.type_;
+    if *type_ != zettel.type_ {
+        ensure_type_rules(
+            &config.type_rules(type_),
+            &zettel.id,
+            type_,
+            &front_matter.tags,
+            &body,
+            false,
+        )?;
+    }
+
+    Ok(front_matter)
+}
+
+pub fn apply_front_matter(
+    conn: &mut SqliteConnection,
+    zettel: &Zettel,
+    front_matter: &FrontMatter,
+) -> Result<Zettel, ZettariumError> {
+    let edited = front_matter.get_zettel();
+    let current_tags = get_tag_by_zettel_id(conn, &zettel.id)?;
+
+    let removed: Vec<_> = current_tags
+        .iter()
+        .filter(|t| {
+            !front_matter
+                .get_tags()
+                .iter()
+                .any(|name| name.eq_ignore_ascii_case(&t.tag_name))
+        })
+        .collect();
+    let added = front_matter.get_tags().iter().any(|name| {
+        !current_tags
+            .iter()
+            .any(|t| t.tag_name.eq_ignore_ascii_case(name))
+    });
+
+    let current_source = get_source_by_zettel_id(conn, &zettel.id)?;
+    // タイトルのない書誌情報はノートのタイトルで保存されるので、そろえてから比べる
+    let edited_source = front_matter.get_source().cloned().map(|mut meta| {
+        meta.title.get_or_insert_with(|| edited.title.clone());
+        meta
+    });
+    let source_changed = current_source.as_ref().map(SourceMeta::from) != edited_source;
+
+    if edited.title == zettel.title
+        && edited.type_ == zettel.type_
+        && removed.is_empty()
+        && !added
+        && !source_changed
+    {
+        // `updated_at` だけ更新する
+        return update_zettel_timestamp_only(conn, &zettel.id);
+    }
+
+    conn.transaction::<Zettel, ZettariumError, _>(|conn| {
+        for tag in &removed {
+            delete_zettel_tag(conn, &zettel.id, &tag.id)?;
+        }
+        if source_changed {
+            // citekeyが変わった場合に古いレコードを残さない
+            delete_sources_by_zettel_id(conn, &zettel.id)?;
+            if let Some(meta) = front_matter.get_source() {
+                upsert_source(conn, &NewSource::from_meta(meta, &zettel.id, &edited.title))?;
+            }
+        }
+        update_zettel(
+            conn,
+            &zettel.id,
+            &edited.title,
+            &edited.type_,
+            front_matter.get_tags(),
+        )
+    })
+}
+
+pub fn update_markdown_file(
+    conn: &mut SqliteConnection,
+    zettel: &Zettel,
+    tags: &[String],
+    dir: &str,
+) -> Result<(), ZettariumError> {
+    let path = rewrite_markdown_file(conn, zettel, tags, dir)?;
+    eprintln!("Markdown saved to {}", path.display());
+    Ok(())
 }
 
-pub fn update_markdown_file(zettel: &Zettel, tags: &[String], dir: &str) -> Result<()> {
-    let (_, body_raw) = parse_markdown(zettel, dir.into())?;
-    let cleaned_body = body_raw
+// front matterをDBの内容で書き直す（表示はしない）
+pub fn rewrite_markdown_file(
+    conn: &mut SqliteConnection,
+    zettel: &Zettel,
+    tags: &[String],
+    dir: &str,
+) -> Result<PathBuf, ZettariumError> {
+    let parsed = parse_markdown(zettel, dir.into())?;
+    // 書誌情報はファイル側のものを引き継ぐ（編集内容はDBに反映済み）
+    let source = parsed.front_matter.and_then(|f| f.source);
+    // 書き出すときに改行が1つ足されるので、末尾の改行は1つにそろえる
+    let mut cleaned_body = parsed
+        .body
         .trim_start_matches('\n')
         .trim_start_matches("\r\n")
+        .trim_end_matches(['\r', '\n'])
         .to_string();
+    cleaned_body.push('\n');
 
     let markdown = Markdown {
         front_matter: FrontMatter {
             zettel: zettel.clone(),
             tags: tags.to_vec(),
+            source,
         },
         body: Body(cleaned_body),
     };
 
-    write_to_markdown(&markdown, dir.into())
+    save_markdown(conn, &markdown, dir.into())
 }
@@ -0,0 +1,75 @@
+use crate::schema::attachments;
+use chrono::NaiveDateTime;
+use diesel::prelude::*;
+use serde::Serialize;
+
+// 画像の拡張子（本文には `![]()` で埋め込む）
+const IMAGE_EXTENSIONS: [&str; 8] = ["png", "jpg", "jpeg", "gif", "svg", "webp", "bmp", "avif"];
+
+#[derive(Debug, Clone, Serialize, Queryable, Selectable)]
+#[diesel(table_name = attachments)]
+pub struct Attachment {
+    pub id: i32,
+    pub zettel_id: String,
+    pub file_name: String,
+    pub sha256: String,
+    pub size: i64,
+    pub created_at: NaiveDateTime,
+    // `z attach --no-link` で添付したもの（本文から参照されていなくてもgcで消さない）
+    pub keep: bool,
+}
+
+impl Attachment {
+    // ノートのあるディレクトリからの相対パス
+    pub fn asset_path(&self) -> String {
+        format!("assets/{}/{}", self.zettel_id, self.file_name)
+    }
+
+    pub fn link_path(&self) -> String {
+        format!("./{}", self.asset_path())
+    }
+
+    pub fn is_image(&self) -> bool {
+        self.file_name
+            .rsplit_once('.')
+            .is_some_and(|(_, ext)| IMAGE_EXTENSIONS.contains(&ext.to_lowercase().as_str()))
+    }
+
+    pub fn markdown_link(&self) -> String {
+        let link = format!("[{}]({})", self.file_name, self.link_path());
+        if self.is_image() {
+            format!("!{}", link)
+        } else {
+            link
+        }
+    }
+}
+
+// Test
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_markdown_link() {
+        let mut attachment = Attachment {
+            id: 1,
+            zettel_id: "20250608T120000".into(),
+            file_name: "screenshot.PNG".into(),
+            sha256: String::new(),
+            size: 0,
+            created_at: NaiveDateTime::default(),
+            keep: false,
+        };
+        assert_eq!(
+            attachment.markdown_link(),
+            "![screenshot.PNG](./assets/20250608T120000/screenshot.PNG)"
+        );
+
+        attachment.file_name = "paper.pdf".into();
+        assert_eq!(
+            attachment.markdown_link(),
+            "[paper.pdf](./assets/20250608T120000/paper.pdf)"
+        );
+    }
+}
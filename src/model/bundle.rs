@@ -0,0 +1,24 @@
+use chrono::NaiveDateTime;
+use serde::{Deserialize, Serialize};
+
+pub const BUNDLE_FORMAT_VERSION: u32 = 1;
+
+// バンドルの先頭に入れる manifest.json
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Manifest {
+    pub format_version: u32,
+    pub app_version: String,
+    pub created_at: NaiveDateTime,
+    // 最後に適用したマイグレーションのバージョン
+    pub schema_version: String,
+    pub note_count: i64,
+    pub files: Vec<ManifestFile>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ManifestFile {
+    // バンドル内のパス（db/ notes/ archive/ のいずれか）
+    pub path: String,
+    pub size: u64,
+    pub sha256: String,
+}
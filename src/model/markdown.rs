@@ -1,5 +1,5 @@
-use crate::Zettel;
-use serde::Serialize;
+use crate::{SourceMeta, Zettel};
+use serde::{Deserialize, Serialize};
 use std::fmt;
 
 #[derive(Debug, Serialize)]
@@ -18,11 +18,15 @@ impl Markdown {
     }
 }
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, Deserialize)]
 pub struct FrontMatter {
     #[serde(flatten)]
     pub zettel: Zettel,
+    #[serde(default)]
     pub tags: Vec<String>,
+    // Literatureノートの書誌情報
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub source: Option<SourceMeta>,
 }
 
 impl FrontMatter {
@@ -33,6 +37,10 @@ impl FrontMatter {
     pub fn get_tags(&self) -> &[String] {
         &self.tags
     }
+
+    pub fn get_source(&self) -> Option<&SourceMeta> {
+        self.source.as_ref()
+    }
 }
 
 #[derive(Debug, Serialize)]
@@ -44,6 +52,27 @@ impl ToString for Body {
     }
 }
 
+// front matterを含むMarkdownファイルの解析結果
+#[derive(Debug)]
+pub struct ParsedMarkdown {
+    pub front_matter: Option<FrontMatter>,
+    pub body: String,
+    pub diagnostics: Vec<Diagnostic>,
+}
+
+// 解析は続行できたが、ユーザーに知らせるべき問題
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Diagnostic {
+    pub line: usize,
+    pub message: String,
+}
+
+impl std::fmt::Display for Diagnostic {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "line {}: {}", self.line, self.message)
+    }
+}
+
 impl std::fmt::Display for Markdown {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         use serde_yaml;
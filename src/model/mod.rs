@@ -1,9 +1,17 @@
+pub mod attachment;
+pub mod bundle;
 pub mod markdown;
+pub mod revision;
+pub mod source;
 pub mod tag;
 pub mod zettel;
 pub mod zettel_tag;
 
+pub use attachment::*;
+pub use bundle::*;
 pub use markdown::*;
+pub use revision::*;
+pub use source::*;
 pub use tag::*;
 pub use zettel::*;
 pub use zettel_tag::*;
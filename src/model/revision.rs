@@ -0,0 +1,74 @@
+use crate::FrontMatter;
+use crate::schema::revisions;
+use chrono::NaiveDateTime;
+use diesel::prelude::*;
+use serde::Serialize;
+
+#[derive(Debug, Clone, Serialize, Queryable, Selectable)]
+#[diesel(table_name = revisions)]
+pub struct Revision {
+    pub id: i32,
+    pub zettel_id: String,
+    // ノートごとの通し番号（1から）
+    pub revision: i32,
+    pub front_matter: String,
+    pub body: String,
+    pub created_at: NaiveDateTime,
+}
+
+impl Revision {
+    // write_to_markdownが書き出したファイルと同じ形
+    pub fn document(&self) -> String {
+        format!("---\n{}---\n\n{}\n", self.front_matter, self.body)
+    }
+
+    // updated_at 以外が同じなら同じ内容とみなす（開いて閉じただけの保存を履歴に残さない）
+    pub fn same_content(&self, front_matter: &FrontMatter, body: &str) -> bool {
+        if self.body != body {
+            return false;
+        }
+        let Ok(mut previous) = serde_yaml::from_str::<FrontMatter>(&self.front_matter) else {
+            return false;
+        };
+        previous.zettel.updated_at = front_matter.zettel.updated_at;
+        serde_yaml::to_string(&previous).ok() == serde_yaml::to_string(front_matter).ok()
+    }
+}
+
+// Test
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_revision_document_round_trip() {
+        let front_matter = "id: 20250608T120000\ntitle: Title\ntype_: Permanent\n\
+                            created_at: 2025-06-08T12:00:00\nupdated_at: 2025-06-08T12:00:00\n\
+                            archived: false\ntags:\n- rust\n";
+        let revision = Revision {
+            id: 1,
+            zettel_id: "20250608T120000".into(),
+            revision: 1,
+            front_matter: front_matter.into(),
+            body: "## Title\n\nbody".into(),
+            created_at: NaiveDateTime::default(),
+        };
+
+        let document = revision.document();
+        let (yaml, body) = document
+            .strip_prefix("---\n")
+            .and_then(|d| d.split_once("---\n"))
+            .unwrap();
+        let parsed: FrontMatter = serde_yaml::from_str(yaml).unwrap();
+        assert_eq!(parsed.get_zettel().title, "Title");
+        assert_eq!(parsed.get_tags(), &vec!["rust".to_string()]);
+        assert_eq!(body, "\n## Title\n\nbody\n");
+
+        let mut edited = parsed;
+        edited.zettel.updated_at = NaiveDateTime::default();
+        assert!(revision.same_content(&edited, "## Title\n\nbody"));
+        assert!(!revision.same_content(&edited, "## Title\n\nbody\n"));
+        edited.tags.push("new".into());
+        assert!(!revision.same_content(&edited, "## Title\n\nbody"));
+    }
+}
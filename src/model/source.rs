@@ -0,0 +1,113 @@
+use crate::schema::sources;
+use diesel::prelude::*;
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Serialize, Queryable, Selectable)]
+#[diesel(table_name = sources)]
+pub struct Source {
+    pub citekey: String,
+    pub zettel_id: Option<String>,
+    pub title: String,
+    // BibTeXと同じく " and " 区切りで保存する
+    pub authors: String,
+    pub year: Option<i32>,
+    pub publisher: Option<String>,
+    pub url: Option<String>,
+    pub doi: Option<String>,
+}
+
+impl Source {
+    pub fn author_list(&self) -> Vec<String> {
+        split_authors(&self.authors)
+    }
+}
+
+pub fn split_authors(authors: &str) -> Vec<String> {
+    authors
+        .split(" and ")
+        .map(|a| a.trim().to_string())
+        .filter(|a| !a.is_empty())
+        .collect()
+}
+
+// front matterの `source:` に書き出す形
+#[derive(Debug, Clone, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub struct SourceMeta {
+    pub citekey: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub title: Option<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub authors: Vec<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub year: Option<i32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub publisher: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub url: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub doi: Option<String>,
+}
+
+impl From<&Source> for SourceMeta {
+    fn from(source: &Source) -> Self {
+        SourceMeta {
+            citekey: source.citekey.clone(),
+            title: Some(source.title.clone()),
+            authors: source.author_list(),
+            year: source.year,
+            publisher: source.publisher.clone(),
+            url: source.url.clone(),
+            doi: source.doi.clone(),
+        }
+    }
+}
+
+impl std::fmt::Display for Source {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        writeln!(f, "Citekey: {}", self.citekey)?;
+        writeln!(f, "Title: {}", self.title)?;
+        writeln!(f, "Authors: {}", self.author_list().join("; "))?;
+        if let Some(year) = self.year {
+            writeln!(f, "Year: {}", year)?;
+        }
+        if let Some(publisher) = &self.publisher {
+            writeln!(f, "Publisher: {}", publisher)?;
+        }
+        if let Some(url) = &self.url {
+            writeln!(f, "URL: {}", url)?;
+        }
+        if let Some(doi) = &self.doi {
+            writeln!(f, "DOI: {}", doi)?;
+        }
+        writeln!(f, "Note: {}", self.zettel_id.as_deref().unwrap_or("-"))?;
+        Ok(())
+    }
+}
+
+// Test
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_source_meta_from_source() {
+        let source = Source {
+            citekey: "ahrens2017".into(),
+            zettel_id: Some("20250608T120000".into()),
+            title: "How to Take Smart Notes".into(),
+            authors: "Ahrens, Sönke".into(),
+            year: Some(2017),
+            publisher: None,
+            url: None,
+            doi: None,
+        };
+
+        let meta = SourceMeta::from(&source);
+        assert_eq!(meta.authors, vec!["Ahrens, Sönke".to_string()]);
+
+        let yaml = serde_yaml::to_string(&meta).unwrap();
+        assert!(yaml.contains("citekey: ahrens2017"));
+        assert!(!yaml.contains("publisher"));
+        assert!(format!("{source}").contains("Year: 2017"));
+    }
+}
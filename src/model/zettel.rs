@@ -1,4 +1,4 @@
-use std::str::FromStr;
+use std::{fmt, str::FromStr};
 
 use chrono::NaiveDateTime;
 use diesel::{
@@ -24,44 +24,117 @@ pub struct Zettel {
     pub created_at: NaiveDateTime,
     pub updated_at: NaiveDateTime,
     pub archived: bool,
+    // ゴミ箱に入れた日時（z trash restore で戻せる）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub deleted_at: Option<NaiveDateTime>,
+    // z process で整理した日時（未整理のFleetingノートは None）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub processed_at: Option<NaiveDateTime>,
 }
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, AsExpression, FromSqlRow)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize, AsExpression, FromSqlRow)]
 #[diesel(sql_type = Text)]
+#[serde(try_from = "String", into = "String")]
 pub enum NoteType {
     Fleeting,
     Permanent,
     Literature,
     Structure,
     Index,
+    // 設定ファイルの [types.<name>] で追加した種類（名前は小文字）
+    Custom(String),
+}
+
+impl NoteType {
+    pub const ALL: [NoteType; 5] = [
+        NoteType::Fleeting,
+        NoteType::Permanent,
+        NoteType::Literature,
+        NoteType::Structure,
+        NoteType::Index,
+    ];
+
+    // DBの値とテンプレートのファイル名に使う名前
+    pub fn name(&self) -> &str {
+        match self {
+            NoteType::Fleeting => "fleeting",
+            NoteType::Permanent => "permanent",
+            NoteType::Literature => "literature",
+            NoteType::Structure => "structure",
+            NoteType::Index => "index",
+            NoteType::Custom(name) => name,
+        }
+    }
+
+    fn builtin(name: &str) -> Option<Self> {
+        match name {
+            "fleeting" | "f" => Some(NoteType::Fleeting),
+            "permanent" | "p" => Some(NoteType::Permanent),
+            "literature" | "l" => Some(NoteType::Literature),
+            "structure" | "s" => Some(NoteType::Structure),
+            "index" | "i" => Some(NoteType::Index),
+            _ => None,
+        }
+    }
+
+    // 設定にない種類の名前も受け付ける（DBやfront matterに残っている値の読み込み用）
+    pub fn from_name(s: &str) -> Result<Self, ZettariumError> {
+        if let Ok(type_) = s.parse() {
+            return Ok(type_);
+        }
+        let name = s.to_lowercase();
+        let valid = name.starts_with(|c: char| c.is_alphabetic())
+            && name
+                .chars()
+                .all(|c| c.is_alphanumeric() || c == '-' || c == '_');
+        if valid {
+            Ok(NoteType::Custom(name))
+        } else {
+            Err(ZettariumError::InvalidNoteType(s.into()))
+        }
+    }
+}
+
+impl fmt::Display for NoteType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut chars = self.name().chars();
+        match chars.next() {
+            Some(first) => write!(f, "{}{}", first.to_uppercase(), chars.as_str()),
+            None => Ok(()),
+        }
+    }
+}
+
+impl From<NoteType> for String {
+    fn from(type_: NoteType) -> Self {
+        type_.to_string()
+    }
 }
 
 impl FromStr for NoteType {
     type Err = ZettariumError;
 
+    // 組み込みの種類だけ（設定で追加した種類は AppConfig::parse_type で解釈する）
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        match s.to_lowercase().as_str() {
-            "fleeting" | "f" => Ok(NoteType::Fleeting),
-            "permanent" | "p" => Ok(NoteType::Permanent),
-            "literature" | "l" => Ok(NoteType::Literature),
-            "structure" | "s" => Ok(NoteType::Structure),
-            "index" | "i" => Ok(NoteType::Index),
-            _ => Err(ZettariumError::InvalidNoteType(s.into())),
-        }
+        NoteType::builtin(&s.to_lowercase())
+            .ok_or_else(|| ZettariumError::InvalidNoteType(s.into()))
+    }
+}
+
+// front matterを手で編集した場合も `permanent` や `p` を受け付ける
+// 設定から消した種類でも読めるようにし、設定にあるかは書き込む側で確かめる
+impl TryFrom<String> for NoteType {
+    type Error = ZettariumError;
+
+    fn try_from(s: String) -> Result<Self, Self::Error> {
+        NoteType::from_name(&s)
     }
 }
 
 // --- ToSql<Text, Sqlite> 実装 ---
 impl ToSql<Text, Sqlite> for NoteType {
     fn to_sql<'b>(&'b self, out: &mut Output<'b, '_, Sqlite>) -> diesel::serialize::Result {
-        let value = match self {
-            NoteType::Fleeting => "fleeting",
-            NoteType::Permanent => "permanent",
-            NoteType::Literature => "literature",
-            NoteType::Structure => "structure",
-            NoteType::Index => "index",
-        };
-        <str as ToSql<Text, Sqlite>>::to_sql(value, out)
+        <str as ToSql<Text, Sqlite>>::to_sql(self.name(), out)
     }
 }
 
@@ -69,14 +142,8 @@ impl ToSql<Text, Sqlite> for NoteType {
 impl FromSql<Text, Sqlite> for NoteType {
     fn from_sql(bytes: <Sqlite as Backend>::RawValue<'_>) -> diesel::deserialize::Result<Self> {
         let s = <*const str as FromSql<Text, Sqlite>>::from_sql(bytes)?;
-        match unsafe { &*s } {
-            "fleeting" => Ok(NoteType::Fleeting),
-            "permanent" => Ok(NoteType::Permanent),
-            "literature" => Ok(NoteType::Literature),
-            "structure" => Ok(NoteType::Structure),
-            "index" => Ok(NoteType::Index),
-            other => Err(format!("Unrecognized NoteType variant: {}", other).into()),
-        }
+        // 設定から消した種類のノートも読めるようにする
+        NoteType::from_name(unsafe { &*s }).map_err(|e| e.to_string().into())
     }
 }
 
@@ -84,7 +151,7 @@ impl std::fmt::Display for Zettel {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         writeln!(f, "ID: {}", self.id)?;
         writeln!(f, "Title: {}", self.title)?;
-        writeln!(f, "Type: {:?}", self.type_)?;
+        writeln!(f, "Type: {}", self.type_)?;
         writeln!(
             f,
             "Created: {}",
@@ -116,9 +183,9 @@ mod tests {
             eprintln!("Failed to load config: {}", e);
             std::process::exit(1);
         });
-        let conn = &mut establish_connection(&config);
+        let conn = &mut establish_connection(&config).unwrap();
         let title = "this is a test";
-        let type_ = "fleeting";
+        let type_ = "fleeting".parse::<NoteType>().unwrap();
         // let tags = Some(vec!["rust", "test"]);
         let tags: Option<Vec<String>> = None;
 
@@ -128,7 +195,7 @@ mod tests {
         }
         let cleaned_tags = dedup_and_warn(tags_str);
 
-        let zettel = create_zettel(conn, title, type_, &cleaned_tags).unwrap();
+        let zettel = create_zettel(conn, title, &type_, &cleaned_tags).unwrap();
 
         let output = format!("{:?}", zettel);
         assert!(output.contains("this is a test"));
@@ -145,4 +212,44 @@ mod tests {
             assert!(msg.contains("Invalid note type"));
         }
     }
+
+    #[test]
+    fn test_custom_note_type() {
+        assert_eq!(NoteType::from_name("p").unwrap(), NoteType::Permanent);
+
+        let project = NoteType::from_name("Project").unwrap();
+        assert_eq!(project, NoteType::Custom("project".into()));
+        assert_eq!(project.name(), "project");
+        assert_eq!(project.to_string(), "Project");
+        assert_eq!(String::from(project), "Project");
+
+        assert!(NoteType::from_name("my type").is_err());
+
+        // 設定で追加した種類と略称は AppConfig::parse_type で解釈する
+        let toml = r#"
+            [paths]
+            db_path = "z.db"
+            zettel_dir = "notes"
+            archive_dir = "archive"
+            [editor]
+            editor = "vi"
+            [types.project]
+            alias = "pr"
+            [types.permanent]
+            alias = "perm"
+        "#;
+        let config: crate::AppConfig = config::Config::builder()
+            .add_source(config::File::from_str(toml, config::FileFormat::Toml))
+            .build()
+            .and_then(|c| c.try_deserialize())
+            .unwrap();
+        assert_eq!(
+            config.parse_type("PR").unwrap(),
+            NoteType::Custom("project".into())
+        );
+        assert_eq!(config.parse_type("perm").unwrap(), NoteType::Permanent);
+        assert_eq!(config.parse_type("p").unwrap(), NoteType::Permanent);
+        assert!(config.parse_type("projcet").is_err());
+        assert!("project".parse::<NoteType>().is_err());
+    }
 }
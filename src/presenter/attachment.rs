@@ -0,0 +1,38 @@
+use crate::Attachment;
+use anyhow::Result;
+use prettytable::{Table, row};
+
+// bodyはリンクされているかどうかの確認に使う
+pub fn print_attachments_as_table(attachments: &[Attachment], body: &str) -> Result<()> {
+    if attachments.is_empty() {
+        println!("No attachments found.");
+        return Ok(());
+    }
+
+    let mut table = Table::new();
+
+    table.add_row(row!["File", "Size", "SHA-256", "Added", "Linked"]);
+
+    for attachment in attachments {
+        let linked = body.contains(&attachment.asset_path());
+        table.add_row(row![
+            attachment.file_name,
+            format_size(attachment.size),
+            &attachment.sha256[..12.min(attachment.sha256.len())],
+            attachment.created_at.format("%Y/%m/%d %H:%M:%S"),
+            linked
+        ]);
+    }
+
+    table.printstd();
+
+    Ok(())
+}
+
+fn format_size(size: i64) -> String {
+    match size {
+        s if s >= 1024 * 1024 => format!("{:.1} MB", s as f64 / (1024.0 * 1024.0)),
+        s if s >= 1024 => format!("{:.1} KB", s as f64 / 1024.0),
+        s => format!("{} B", s),
+    }
+}
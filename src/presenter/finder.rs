@@ -4,35 +4,145 @@ use std::io::Write;
 use std::process::{Command, Stdio};
 use which::which;
 
-use crate::{AppConfig, Zettel, list_zettels, parse_markdown};
+use crate::{AppConfig, Zettel, list_zettels, parse_markdown, score_fields};
 
-pub fn ensure_fzf_installed() -> Result<()> {
-    if which("fzf").is_err() {
-        anyhow::bail!("fzf not found. Please install it (e.g. `brew install fzf`).");
+// Finderに渡す候補（lineはfzfなどに表示する1行）
+#[derive(Debug, Clone)]
+pub struct FinderItem {
+    pub id: String,
+    pub title: String,
+    pub tags: Vec<String>,
+    pub line: String,
+}
+
+// 候補から選ばれたもの（非対話の場合は順位の高いもの）のindexを返す
+pub trait Finder {
+    fn find(&self, items: &[FinderItem], query: &str) -> Result<Vec<usize>>;
+}
+
+// 対話的に選ぶ（fzfが必要）
+pub struct FzfFinder<'a> {
+    pub config: &'a AppConfig,
+    // trueならTab / Shift-Tabで複数選べる
+    pub multi: bool,
+}
+
+impl Finder for FzfFinder<'_> {
+    fn find(&self, items: &[FinderItem], query: &str) -> Result<Vec<usize>> {
+        ensure_fzf_installed(self.config)?;
+        let lines: Vec<String> = items.iter().map(|i| i.line.clone()).collect();
+        let Some(selected) = run_fzf(&lines, query, self.multi, self.config)? else {
+            return Ok(vec![]);
+        };
+        // 1行に1件、行の先頭はID
+        Ok(selected
+            .lines()
+            .filter_map(|line| {
+                let id = line.split_whitespace().next()?;
+                items.iter().position(|i| i.id == id)
+            })
+            .collect())
+    }
+}
+
+// プロセス内でスコアを付けて上位limit件を返す（スクリプト・CI向け）
+pub struct FuzzyFinder {
+    pub limit: usize,
+    // trueならタイトルだけを対象にする
+    pub title_only: bool,
+}
+
+impl Finder for FuzzyFinder {
+    fn find(&self, items: &[FinderItem], query: &str) -> Result<Vec<usize>> {
+        let mut ranked: Vec<(i64, usize)> = items
+            .iter()
+            .enumerate()
+            .filter_map(|(index, item)| {
+                let tags: Vec<String> = item.tags.iter().map(|t| format!("#{}", t)).collect();
+                let mut fields = vec![item.title.as_str()];
+                if !self.title_only {
+                    fields.push(item.id.as_str());
+                    fields.extend(tags.iter().map(String::as_str));
+                }
+                score_fields(query, &fields).map(|score| (score, index))
+            })
+            .collect();
+        // 同点なら新しいノートを先に
+        ranked.sort_by(|(sa, ia), (sb, ib)| sb.cmp(sa).then(items[*ib].id.cmp(&items[*ia].id)));
+        Ok(ranked
+            .into_iter()
+            .take(self.limit)
+            .map(|(_, index)| index)
+            .collect())
+    }
+}
+
+pub fn ensure_fzf_installed(config: &AppConfig) -> Result<()> {
+    let command = &config.finder.command;
+    if which(command).is_err() {
+        anyhow::bail!(
+            "{} not found. Please install it (e.g. `brew install fzf`), \
+             or use `--first` / `--top N` or `z tui` instead.",
+            command
+        );
     }
     Ok(())
 }
 
-pub fn run_fzf(zettel_lines: &[String], config: &AppConfig) -> Result<Option<String>> {
+// プレビューのコマンドを組み立てる
+// {path} はアーカイブ済みも含めた実際のファイルパス（`z path` で解決）、{id} はノートID
+pub fn preview_command(config: &AppConfig) -> Option<String> {
+    let template = match &config.finder.preview {
+        Some(preview) if preview.trim().is_empty() => return None,
+        Some(preview) => preview.clone(),
+        // bat（Debian系では batcat）がなければ zettarium 自身で表示する
+        None => ["bat", "batcat"]
+            .into_iter()
+            .find(|bat| which(bat).is_ok())
+            .map(|bat| format!("{} --style=plain --color=always {{path}}", bat))
+            .unwrap_or_else(|| "{zettarium} view {id}".into()),
+    };
+
+    let exe = std::env::current_exe()
+        .map(|p| shell_quote(&p.display().to_string()))
+        .unwrap_or_else(|_| "zettarium".into());
+    // fzfは {1}（1列目 = ID）をクォートして埋め込む
+    Some(
+        template
+            .replace("{path}", &format!("\"$({} path {{1}})\"", exe))
+            .replace("{id}", "{1}")
+            .replace("{zettarium}", &exe),
+    )
+}
+
+fn shell_quote(s: &str) -> String {
+    format!("'{}'", s.replace('\'', "'\\''"))
+}
+
+pub fn run_fzf(
+    zettel_lines: &[String],
+    query: &str,
+    multi: bool,
+    config: &AppConfig,
+) -> Result<Option<String>> {
+    let finder = &config.finder;
+
     // fzfプロセス開始
-    let mut child = Command::new("fzf")
-        .args(&[
-            "--ansi",
-            "--with-nth=3..", // 3列目以降（タイトルなど）を対象に検索
-            "--delimiter=|",
-            "--preview",
-            &format!(
-                "bat --style=plain --color=always {}/$(echo {{}} | cut -d ' ' -f1).md",
-                config.paths.zettel_dir
-            ),
-            "--preview-window=right:70%",
-            "--prompt",
-            "🔍 Search: ",
-        ])
+    let mut command = Command::new(&finder.command);
+    command.args(&finder.options);
+    if let Some(preview) = preview_command(config) {
+        command.arg("--preview").arg(preview);
+    }
+    if multi {
+        command.arg("--multi");
+    }
+    let mut child = command
+        .arg("--query")
+        .arg(query)
         .stdin(Stdio::piped())
         .stdout(Stdio::piped())
         .spawn()
-        .context("Failed to spawn fzf")?;
+        .with_context(|| format!("Failed to spawn {}", finder.command))?;
 
     // fzfの標準入力に候補を書き込む
     {
@@ -64,7 +174,7 @@ pub fn find_backlinks(
     let mut backlinks = vec![];
 
     for z in &all_zettels {
-        let (_, body) = parse_markdown(z, config.paths.zettel_dir.clone().into())?;
+        let body = parse_markdown(z, config.paths.zettel_dir.clone().into())?.body;
         if body.contains(&format!("./{}.md", target_id)) {
             backlinks.push(z.clone());
         }
@@ -0,0 +1,83 @@
+// fzfを使わずに候補を絞り込むための簡易fuzzy matcher
+
+// patternの文字がtextに順番通り現れればスコアを返す（大文字小文字は区別しない）
+pub fn fuzzy_score(pattern: &str, text: &str) -> Option<i64> {
+    let pattern = pattern.to_lowercase();
+    let text = text.to_lowercase();
+    if pattern.is_empty() {
+        return Some(0);
+    }
+
+    let chars: Vec<char> = text.chars().collect();
+    let is_boundary = |i: usize| i == 0 || !chars[i - 1].is_alphanumeric();
+
+    // 部分文字列として含まれていれば大きく加点する
+    if let Some(byte_pos) = text.find(&pattern) {
+        let pos = text[..byte_pos].chars().count();
+        let len = pattern.chars().count() as i64;
+        let mut score = 100 + len * 10 - (pos as i64).min(20);
+        if is_boundary(pos) {
+            score += 30;
+        }
+        if text == pattern {
+            score += 50;
+        }
+        return Some(score);
+    }
+
+    // 連続・単語の先頭での一致を優先し、間が空くほど減点する
+    let mut score = 0;
+    let mut last: Option<usize> = None;
+    let mut pos = 0;
+    for p in pattern.chars() {
+        let found = (pos..chars.len()).find(|&i| chars[i] == p)?;
+        score += 10;
+        if last.is_some_and(|l| l + 1 == found) {
+            score += 15;
+        } else if is_boundary(found) {
+            score += 20;
+        }
+        if let Some(l) = last {
+            score -= (found - l - 1).min(10) as i64;
+        }
+        last = Some(found);
+        pos = found + 1;
+    }
+    Some(score)
+}
+
+// 空白区切りの語がすべてどれかのフィールドに一致したときだけスコアを返す
+pub fn score_fields(query: &str, fields: &[&str]) -> Option<i64> {
+    query.split_whitespace().try_fold(0, |total, term| {
+        fields
+            .iter()
+            .filter_map(|field| fuzzy_score(term, field))
+            .max()
+            .map(|score| total + score)
+    })
+}
+
+// Test
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_fuzzy_score_ranking() {
+        assert!(fuzzy_score("xyz", "Rust async").is_none());
+        assert!(fuzzy_score("rsa", "Rust async").is_some());
+
+        // 部分一致 > 単語の先頭 > ばらばらの一致
+        let substring = fuzzy_score("async", "Rust async").unwrap();
+        let initials = fuzzy_score("ra", "Rust async").unwrap();
+        let scattered = fuzzy_score("ra", "Error handling").unwrap();
+        assert!(substring > initials);
+        assert!(initials > scattered);
+
+        assert_eq!(
+            score_fields("rust async", &["Tokio runtime", "#rust", "#async"]),
+            Some(fuzzy_score("rust", "#rust").unwrap() + fuzzy_score("async", "#async").unwrap())
+        );
+        assert!(score_fields("rust go", &["Rust async"]).is_none());
+    }
+}
@@ -1,7 +1,17 @@
+pub mod attachment;
 pub mod finder;
+pub mod fuzzy;
 pub mod markdown;
+pub mod prompt;
+pub mod revision;
+pub mod source;
 pub mod zettel;
 
+pub use attachment::*;
 pub use finder::*;
+pub use fuzzy::*;
 pub use markdown::*;
+pub use prompt::*;
+pub use revision::*;
+pub use source::*;
 pub use zettel::*;
@@ -0,0 +1,27 @@
+use std::io::{Write, stdin, stdout};
+
+use crate::ZettariumError;
+
+// y/n で確認する（空の入力は default、入力が終わった（EOF）場合は「いいえ」）
+pub fn confirm(message: &str, default: bool) -> Result<bool, ZettariumError> {
+    let choices = if default { "[Y/n]" } else { "[y/N]" };
+    print!("{} {}: ", message, choices);
+    stdout()
+        .flush()
+        .map_err(|e| ZettariumError::io("<stdout>", e))?;
+
+    let mut input = String::new();
+    let read = stdin()
+        .read_line(&mut input)
+        .map_err(|e| ZettariumError::io("<stdin>", e))?;
+    if read == 0 {
+        println!();
+        return Ok(false);
+    }
+
+    match input.trim().to_lowercase().as_str() {
+        "" => Ok(default),
+        "y" | "yes" => Ok(true),
+        _ => Ok(false),
+    }
+}
@@ -0,0 +1,58 @@
+use crate::{FrontMatter, GitCommit, Revision};
+use anyhow::Result;
+use prettytable::{Table, row};
+
+pub fn print_revisions_as_table(revisions: &[Revision]) -> Result<()> {
+    if revisions.is_empty() {
+        println!("No revisions found.");
+        return Ok(());
+    }
+
+    let mut table = Table::new();
+
+    table.add_row(row!["Rev", "Saved", "Title", "Tags", "Lines"]);
+
+    for revision in revisions {
+        // 古いリビジョンが読めなくても一覧は出す
+        let front_matter = serde_yaml::from_str::<FrontMatter>(&revision.front_matter).ok();
+        let title = front_matter
+            .as_ref()
+            .map(|f| f.get_zettel().title.clone())
+            .unwrap_or_else(|| "-".into());
+        let tags = front_matter
+            .as_ref()
+            .map(|f| f.get_tags().join(","))
+            .unwrap_or_default();
+
+        table.add_row(row![
+            revision.revision,
+            revision.created_at.format("%Y/%m/%d %H:%M:%S"),
+            title,
+            tags,
+            revision.body.lines().count()
+        ]);
+    }
+
+    table.printstd();
+
+    Ok(())
+}
+
+pub fn print_commits_as_table(commits: &[GitCommit]) -> Result<()> {
+    if commits.is_empty() {
+        println!("No commits found.");
+        return Ok(());
+    }
+
+    let mut table = Table::new();
+
+    table.add_row(row!["Commit", "Date", "Message"]);
+
+    for commit in commits {
+        table.add_row(row![commit.hash, commit.date, commit.subject]);
+    }
+
+    table.printstd();
+
+    Ok(())
+}
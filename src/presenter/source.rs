@@ -0,0 +1,26 @@
+use crate::Source;
+use anyhow::Result;
+use prettytable::{Table, row};
+
+pub fn print_sources_as_table(sources: &[Source]) -> Result<()> {
+    if sources.is_empty() {
+        println!("No sources found.");
+        return Ok(());
+    }
+
+    let mut table = Table::new();
+
+    table.add_row(row!["Citekey", "Year", "Authors", "Title", "Note"]);
+
+    for source in sources {
+        let year = source.year.map(|y| y.to_string()).unwrap_or_default();
+        let authors = source.author_list().join("; ");
+        let note = source.zettel_id.clone().unwrap_or_default();
+
+        table.add_row(row![source.citekey, year, authors, source.title, note]);
+    }
+
+    table.printstd();
+
+    Ok(())
+}
@@ -16,7 +16,7 @@ pub fn print_zettels_as_table(conn: &mut SqliteConnection, zettels: &Vec<Zettel>
     ]);
 
     for zettel in zettels {
-        let type_ = format!("{:?}", zettel.type_);
+        let type_ = zettel.type_.to_string();
         let format_created = zettel.created_at.format("%Y/%m/%d %H:%M:%S").to_string();
         let format_updated = zettel.updated_at.format("%Y/%m/%d %H:%M:%S").to_string();
 
@@ -39,3 +39,33 @@ pub fn print_zettels_as_table(conn: &mut SqliteConnection, zettels: &Vec<Zettel>
 
     Ok(())
 }
+
+pub fn print_trashed_zettels_as_table(zettels: &[Zettel]) -> Result<()> {
+    if zettels.is_empty() {
+        println!("Trash is empty.");
+        return Ok(());
+    }
+
+    let mut table = Table::new();
+
+    table.add_row(row!["ID", "Title", "Type", "Archived", "Deleted"]);
+
+    for zettel in zettels {
+        let format_deleted = zettel
+            .deleted_at
+            .map(|d| d.format("%Y/%m/%d %H:%M:%S").to_string())
+            .unwrap_or_default();
+
+        table.add_row(row![
+            zettel.id,
+            zettel.title,
+            zettel.type_.to_string(),
+            zettel.archived,
+            format_deleted
+        ]);
+    }
+
+    table.printstd();
+
+    Ok(())
+}
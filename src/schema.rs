@@ -1,5 +1,41 @@
 // @generated automatically by Diesel CLI.
 
+diesel::table! {
+    attachments (id) {
+        id -> Integer,
+        zettel_id -> Text,
+        file_name -> Text,
+        sha256 -> Text,
+        size -> BigInt,
+        created_at -> Timestamp,
+        keep -> Bool,
+    }
+}
+
+diesel::table! {
+    revisions (id) {
+        id -> Integer,
+        zettel_id -> Text,
+        revision -> Integer,
+        front_matter -> Text,
+        body -> Text,
+        created_at -> Timestamp,
+    }
+}
+
+diesel::table! {
+    sources (citekey) {
+        citekey -> Text,
+        zettel_id -> Nullable<Text>,
+        title -> Text,
+        authors -> Text,
+        year -> Nullable<Integer>,
+        publisher -> Nullable<Text>,
+        url -> Nullable<Text>,
+        doi -> Nullable<Text>,
+    }
+}
+
 diesel::table! {
     tags (id) {
         id -> Text,
@@ -23,13 +59,21 @@ diesel::table! {
         created_at -> Timestamp,
         updated_at -> Timestamp,
         archived -> Bool,
+        deleted_at -> Nullable<Timestamp>,
+        processed_at -> Nullable<Timestamp>,
     }
 }
 
+diesel::joinable!(attachments -> zettels (zettel_id));
+diesel::joinable!(revisions -> zettels (zettel_id));
+diesel::joinable!(sources -> zettels (zettel_id));
 diesel::joinable!(zettel_tags -> tags (tag_id));
 diesel::joinable!(zettel_tags -> zettels (zettel_id));
 
 diesel::allow_tables_to_appear_in_same_query!(
+    attachments,
+    revisions,
+    sources,
     tags,
     zettel_tags,
     zettels,
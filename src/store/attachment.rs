@@ -0,0 +1,89 @@
+use crate::{
+    Attachment, ZettariumError,
+    schema::attachments::{self, dsl::*},
+};
+use chrono::{Local, NaiveDateTime};
+use diesel::{SqliteConnection, prelude::*};
+
+#[derive(Debug, Insertable)]
+#[diesel(table_name = attachments)]
+pub struct NewAttachment {
+    pub zettel_id: String,
+    pub file_name: String,
+    pub sha256: String,
+    pub size: i64,
+    pub created_at: NaiveDateTime,
+    pub keep: bool,
+}
+
+pub fn create_attachment(
+    conn: &mut SqliteConnection,
+    note_id: &str,
+    name: &str,
+    hash: &str,
+    file_size: i64,
+    keep_: bool,
+) -> Result<Attachment, ZettariumError> {
+    let new_attachment = NewAttachment {
+        zettel_id: note_id.to_string(),
+        file_name: name.to_string(),
+        sha256: hash.to_string(),
+        size: file_size,
+        created_at: Local::now().naive_local(),
+        keep: keep_,
+    };
+    let created = diesel::insert_into(attachments::table)
+        .values(&new_attachment)
+        .returning(Attachment::as_select())
+        .get_result(conn)?;
+
+    Ok(created)
+}
+
+pub fn list_attachments(
+    conn: &mut SqliteConnection,
+    note_id: &str,
+) -> Result<Vec<Attachment>, ZettariumError> {
+    Ok(attachments
+        .filter(zettel_id.eq(note_id))
+        .order(id.asc())
+        .select(Attachment::as_select())
+        .load(conn)?)
+}
+
+pub fn list_all_attachments(
+    conn: &mut SqliteConnection,
+) -> Result<Vec<Attachment>, ZettariumError> {
+    Ok(attachments
+        .order(id.asc())
+        .select(Attachment::as_select())
+        .load(conn)?)
+}
+
+// 同じ内容のファイルが既に添付されていればそれを返す
+pub fn find_attachment_by_hash(
+    conn: &mut SqliteConnection,
+    note_id: &str,
+    hash: &str,
+) -> Result<Option<Attachment>, ZettariumError> {
+    Ok(attachments
+        .filter(zettel_id.eq(note_id))
+        .filter(sha256.eq(hash))
+        .select(Attachment::as_select())
+        .first(conn)
+        .optional()?)
+}
+
+pub fn delete_attachment(
+    conn: &mut SqliteConnection,
+    attachment_id: i32,
+) -> Result<usize, ZettariumError> {
+    Ok(diesel::delete(attachments.find(attachment_id)).execute(conn)?)
+}
+
+pub fn delete_attachments_by_zettel_id(
+    conn: &mut SqliteConnection,
+    note_id: &str,
+) -> Result<usize, ZettariumError> {
+    Ok(diesel::delete(attachments.filter(zettel_id.eq(note_id))).execute(conn)?)
+}
@@ -0,0 +1,536 @@
+use chrono::Local;
+use diesel::{Connection, SqliteConnection};
+use sha2::{Digest, Sha256};
+use std::{
+    collections::HashMap,
+    fs::{self, File},
+    io,
+    path::{Component, Path, PathBuf},
+};
+
+use crate::{
+    AppConfig, BUNDLE_FORMAT_VERSION, Manifest, ManifestFile, ZettariumError, count_zettels,
+    store::{known_schema_versions, schema_version, snapshot_database},
+};
+
+const MANIFEST: &str = "manifest.json";
+const DB_ENTRY: &str = "db/zettarium.db";
+const NOTES_PREFIX: &str = "notes";
+const ARCHIVE_PREFIX: &str = "archive";
+const TRASH_PREFIX: &str = "trash";
+// gitの履歴はバンドルに含めず、restoreでも既存のものを残す
+const GIT_DIR: &str = ".git";
+
+// 途中で失敗しても作業ディレクトリを残さない
+struct WorkDir(PathBuf);
+
+impl WorkDir {
+    fn new(kind: &str) -> Result<Self, ZettariumError> {
+        let path = std::env::temp_dir().join(format!(
+            "zettarium-{}-{}-{}",
+            kind,
+            std::process::id(),
+            Local::now().format("%Y%m%d%H%M%S%f")
+        ));
+        fs::create_dir_all(&path).map_err(|e| ZettariumError::io(&path, e))?;
+        Ok(WorkDir(path))
+    }
+}
+
+impl Drop for WorkDir {
+    fn drop(&mut self) {
+        let _ = fs::remove_dir_all(&self.0);
+    }
+}
+
+// DB / zettel_dir / archive_dir / trash_dir を manifest と一緒に tar.zst にまとめる
+pub fn create_bundle(
+    conn: &mut SqliteConnection,
+    config: &AppConfig,
+    output: &Path,
+) -> Result<Manifest, ZettariumError> {
+    let work = WorkDir::new("bundle")?;
+    let snapshot = work.0.join("zettarium.db");
+    snapshot_database(conn, &snapshot)?;
+
+    let mut entries: Vec<(String, PathBuf)> = vec![(DB_ENTRY.to_string(), snapshot)];
+    let targets = bundle_dirs(config);
+    let dirs: Vec<PathBuf> = targets.iter().map(|(_, dir)| dir.clone()).collect();
+    for (prefix, dir) in &targets {
+        collect_files(dir, prefix, &dirs, &mut entries)?;
+    }
+
+    let mut files = vec![];
+    for (name, path) in &entries {
+        let (size, sha256) = hash_file(path)?;
+        files.push(ManifestFile {
+            path: name.clone(),
+            size,
+            sha256,
+        });
+    }
+
+    let manifest = Manifest {
+        format_version: BUNDLE_FORMAT_VERSION,
+        app_version: env!("CARGO_PKG_VERSION").to_string(),
+        created_at: Local::now().naive_local(),
+        schema_version: schema_version(conn)?,
+        note_count: count_zettels(conn)?,
+        files,
+    };
+
+    let file = File::create(output).map_err(|e| ZettariumError::io(output, e))?;
+    let encoder = zstd::Encoder::new(file, 0).map_err(|e| ZettariumError::io(output, e))?;
+    let mut builder = tar::Builder::new(encoder);
+
+    let json = serde_json::to_vec_pretty(&manifest).expect("manifest is always serializable");
+    let mut header = tar::Header::new_gnu();
+    header.set_size(json.len() as u64);
+    header.set_mode(0o644);
+    header.set_mtime(Local::now().timestamp().max(0) as u64);
+    header.set_cksum();
+    builder
+        .append_data(&mut header, MANIFEST, json.as_slice())
+        .map_err(|e| ZettariumError::io(output, e))?;
+
+    for (name, path) in &entries {
+        builder
+            .append_path_with_name(path, name)
+            .map_err(|e| ZettariumError::io(path, e))?;
+    }
+
+    let encoder = builder
+        .into_inner()
+        .map_err(|e| ZettariumError::io(output, e))?;
+    encoder
+        .finish()
+        .map_err(|e| ZettariumError::io(output, e))?;
+
+    Ok(manifest)
+}
+
+// バンドルを検証してから設定されたパスに展開する
+pub fn restore_bundle(
+    config: &AppConfig,
+    input: &Path,
+    force: bool,
+) -> Result<Manifest, ZettariumError> {
+    let work = WorkDir::new("restore")?;
+
+    // 1. 作業ディレクトリに展開しながらチェックサムを計算する
+    let file = File::open(input).map_err(|e| ZettariumError::io(input, e))?;
+    let decoder = zstd::Decoder::new(file).map_err(|e| ZettariumError::io(input, e))?;
+    let mut archive = tar::Archive::new(decoder);
+
+    let mut manifest: Option<Manifest> = None;
+    let mut found: HashMap<String, (u64, String)> = HashMap::new();
+    let entries = archive
+        .entries()
+        .map_err(|e| ZettariumError::BundleInvalid(e.to_string()))?;
+    for entry in entries {
+        let mut entry = entry.map_err(|e| ZettariumError::BundleInvalid(e.to_string()))?;
+        if entry.header().entry_type().is_dir() {
+            continue;
+        }
+        let path = entry
+            .path()
+            .map_err(|e| ZettariumError::BundleInvalid(e.to_string()))?
+            .into_owned();
+        if !path.components().all(|c| matches!(c, Component::Normal(_))) {
+            return Err(ZettariumError::BundleInvalid(format!(
+                "unsafe path {}",
+                path.display()
+            )));
+        }
+        let name = path.to_string_lossy().replace('\\', "/");
+
+        if name == MANIFEST {
+            let parsed = serde_json::from_reader(&mut entry)
+                .map_err(|e| ZettariumError::BundleInvalid(format!("{}: {}", MANIFEST, e)))?;
+            manifest = Some(parsed);
+            continue;
+        }
+
+        let dest = work.0.join(&path);
+        if let Some(parent) = dest.parent() {
+            fs::create_dir_all(parent).map_err(|e| ZettariumError::io(parent, e))?;
+        }
+        entry
+            .unpack(&dest)
+            .map_err(|e| ZettariumError::BundleInvalid(format!("{}: {}", name, e)))?;
+        found.insert(name, hash_file(&dest)?);
+    }
+
+    // 2. manifestと中身を突き合わせる
+    let manifest = manifest
+        .ok_or_else(|| ZettariumError::BundleInvalid(format!("{} is missing", MANIFEST)))?;
+    validate_manifest(&manifest, &found)?;
+
+    let staged_db = work.0.join(DB_ENTRY);
+    let mut staged = SqliteConnection::establish(&staged_db.display().to_string())
+        .map_err(|e| ZettariumError::BundleInvalid(format!("database cannot be opened: {}", e)))?;
+    let note_count = count_zettels(&mut staged)?;
+    if note_count != manifest.note_count {
+        return Err(ZettariumError::BundleInvalid(format!(
+            "manifest lists {} notes but the database has {}",
+            manifest.note_count, note_count
+        )));
+    }
+    drop(staged);
+
+    // 3. 既存のデータは --force がなければ上書きしない
+    let db_path = PathBuf::from(&config.paths.db_path);
+    let targets = bundle_dirs(config);
+    if !force {
+        if db_path.exists() {
+            return Err(ZettariumError::RestoreTargetExists(db_path));
+        }
+        for (_, dir) in &targets {
+            if !is_empty_dir(dir)? {
+                return Err(ZettariumError::RestoreTargetExists(dir.clone()));
+            }
+        }
+    }
+
+    // 4. 設定されたパスの隣にコピーしてから入れ替える（途中で失敗しても既存のデータは残る）
+    let mut staging = Staging::default();
+    let staged_paths: Vec<PathBuf> = targets
+        .iter()
+        .map(|(_, dir)| staged_dir(dir, &targets))
+        .collect();
+    for ((prefix, dir), staged) in targets.iter().zip(&staged_paths) {
+        if is_outermost(dir, &targets) {
+            staging.dirs.push((dir.clone(), staged.clone()));
+        }
+        fs::create_dir_all(staged).map_err(|e| ZettariumError::io(staged, e))?;
+        for file in manifest
+            .files
+            .iter()
+            .filter(|f| f.path.starts_with(&format!("{}/", prefix)))
+        {
+            let relative = &file.path[prefix.len() + 1..];
+            let dest = staged.join(relative);
+            if let Some(parent) = dest.parent() {
+                fs::create_dir_all(parent).map_err(|e| ZettariumError::io(parent, e))?;
+            }
+            fs::copy(work.0.join(&file.path), &dest).map_err(|e| ZettariumError::io(&dest, e))?;
+        }
+    }
+    if let Some(parent) = db_path.parent() {
+        fs::create_dir_all(parent).map_err(|e| ZettariumError::io(parent, e))?;
+    }
+    let db_staged = sibling(&db_path, "restore");
+    staging.db = Some(db_staged.clone());
+    fs::copy(&staged_db, &db_staged).map_err(|e| ZettariumError::io(&db_staged, e))?;
+
+    staging.swap(&db_path)?;
+    Ok(manifest)
+}
+
+// restore中のファイル（失敗した場合はDropで片付ける）
+#[derive(Default)]
+struct Staging {
+    // (設定されたパス, コピー先)
+    dirs: Vec<(PathBuf, PathBuf)>,
+    db: Option<PathBuf>,
+}
+
+impl Staging {
+    fn swap(&mut self, db_path: &Path) -> Result<(), ZettariumError> {
+        // 既存のディレクトリを退避してから入れ替え、失敗したら元に戻す
+        let mut swapped: Vec<(PathBuf, PathBuf)> = vec![];
+        let mut result = Ok(());
+        for (dir, staged) in &self.dirs {
+            let old = sibling(dir, "old");
+            result = swap_dir(dir, staged, &old);
+            if result.is_err() {
+                break;
+            }
+            swapped.push((dir.clone(), old));
+        }
+        if result.is_ok()
+            && let Some(db) = &self.db
+        {
+            result = fs::rename(db, db_path).map_err(|e| ZettariumError::io(db_path, e));
+        }
+        if let Err(e) = result {
+            for (dir, old) in swapped.iter().rev() {
+                if old.exists() {
+                    let _ = fs::rename(dir.join(GIT_DIR), old.join(GIT_DIR));
+                    let _ = fs::remove_dir_all(dir);
+                    let _ = fs::rename(old, dir);
+                } else {
+                    let _ = fs::remove_dir_all(dir);
+                }
+            }
+            return Err(e);
+        }
+        self.dirs.clear();
+        self.db = None;
+        for (_, old) in swapped {
+            if old.exists() {
+                let _ = fs::remove_dir_all(old);
+            }
+        }
+        Ok(())
+    }
+}
+
+impl Drop for Staging {
+    fn drop(&mut self) {
+        for (_, staged) in &self.dirs {
+            let _ = fs::remove_dir_all(staged);
+        }
+        if let Some(db) = &self.db {
+            let _ = fs::remove_file(db);
+        }
+    }
+}
+
+fn swap_dir(dir: &Path, staged: &Path, old: &Path) -> Result<(), ZettariumError> {
+    if dir.exists() {
+        fs::rename(dir, old).map_err(|e| ZettariumError::io(dir, e))?;
+        // zettel_dirのgitリポジトリは引き継ぐ
+        let git = old.join(GIT_DIR);
+        if git.exists() {
+            fs::rename(&git, staged.join(GIT_DIR)).map_err(|e| ZettariumError::io(&git, e))?;
+        }
+    }
+    if let Err(e) = fs::rename(staged, dir) {
+        if old.exists() {
+            let _ = fs::rename(old.join(GIT_DIR), staged.join(GIT_DIR));
+            let _ = fs::rename(old, dir);
+        }
+        return Err(ZettariumError::io(dir, e));
+    }
+    Ok(())
+}
+
+// バンドルに入れるディレクトリ（trash_dirは既定ではzettel_dirの中）
+fn bundle_dirs(config: &AppConfig) -> Vec<(&'static str, PathBuf)> {
+    vec![
+        (NOTES_PREFIX, PathBuf::from(&config.paths.zettel_dir)),
+        (ARCHIVE_PREFIX, PathBuf::from(&config.paths.archive_dir)),
+        (TRASH_PREFIX, PathBuf::from(config.paths.trash_dir())),
+    ]
+}
+
+fn is_outermost(dir: &Path, targets: &[(&str, PathBuf)]) -> bool {
+    !targets
+        .iter()
+        .any(|(_, other)| other != dir && dir.starts_with(other))
+}
+
+// 他のディレクトリの中にあるものは、そのディレクトリのコピー先の中に置く
+fn staged_dir(dir: &Path, targets: &[(&str, PathBuf)]) -> PathBuf {
+    match targets
+        .iter()
+        .find(|(_, other)| other != dir && dir.starts_with(other) && is_outermost(other, targets))
+    {
+        Some((_, outer)) => staged_dir(outer, targets).join(dir.strip_prefix(outer).unwrap_or(dir)),
+        None => sibling(dir, "restore"),
+    }
+}
+
+fn sibling(path: &Path, kind: &str) -> PathBuf {
+    let name = path.file_name().unwrap_or_default().to_string_lossy();
+    path.with_file_name(format!(".{}.{}-{}", name, kind, std::process::id()))
+}
+
+fn validate_manifest(
+    manifest: &Manifest,
+    found: &HashMap<String, (u64, String)>,
+) -> Result<(), ZettariumError> {
+    if manifest.format_version != BUNDLE_FORMAT_VERSION {
+        return Err(ZettariumError::BundleInvalid(format!(
+            "unsupported format version {}",
+            manifest.format_version
+        )));
+    }
+    if !known_schema_versions()?.contains(&manifest.schema_version) {
+        return Err(ZettariumError::BundleInvalid(format!(
+            "schema version {} is unknown to this zettarium ({}); upgrade zettarium first",
+            manifest.schema_version,
+            env!("CARGO_PKG_VERSION")
+        )));
+    }
+    if !manifest.files.iter().any(|f| f.path == DB_ENTRY) {
+        return Err(ZettariumError::BundleInvalid(format!(
+            "{} is missing",
+            DB_ENTRY
+        )));
+    }
+
+    for file in &manifest.files {
+        match found.get(&file.path) {
+            None => {
+                return Err(ZettariumError::BundleInvalid(format!(
+                    "{} is missing",
+                    file.path
+                )));
+            }
+            Some((size, sha256)) if *size != file.size || *sha256 != file.sha256 => {
+                return Err(ZettariumError::BundleInvalid(format!(
+                    "checksum mismatch for {}",
+                    file.path
+                )));
+            }
+            Some(_) => {}
+        }
+    }
+    if let Some(extra) = found
+        .keys()
+        .find(|name| !manifest.files.iter().any(|f| &f.path == *name))
+    {
+        return Err(ZettariumError::BundleInvalid(format!(
+            "{} is not listed in the manifest",
+            extra
+        )));
+    }
+    Ok(())
+}
+
+// `skip` 以下（zettel_dir内に置かれたarchive_dirなど）と `.git` は含めない
+fn collect_files(
+    dir: &Path,
+    prefix: &str,
+    skip: &[PathBuf],
+    entries: &mut Vec<(String, PathBuf)>,
+) -> Result<(), ZettariumError> {
+    if !dir.exists() {
+        return Ok(());
+    }
+    let mut paths = vec![];
+    for entry in fs::read_dir(dir).map_err(|e| ZettariumError::io(dir, e))? {
+        paths.push(entry.map_err(|e| ZettariumError::io(dir, e))?.path());
+    }
+    paths.sort();
+
+    for path in paths {
+        if skip.contains(&path) || path.file_name() == Some(GIT_DIR.as_ref()) {
+            continue;
+        }
+        let name = format!(
+            "{}/{}",
+            prefix,
+            path.file_name().unwrap_or_default().to_string_lossy()
+        );
+        if path.is_dir() {
+            collect_files(&path, &name, skip, entries)?;
+        } else {
+            entries.push((name, path));
+        }
+    }
+    Ok(())
+}
+
+pub fn hash_file(path: &Path) -> Result<(u64, String), ZettariumError> {
+    let mut file = File::open(path).map_err(|e| ZettariumError::io(path, e))?;
+    let mut hasher = Sha256::new();
+    let size = io::copy(&mut file, &mut hasher).map_err(|e| ZettariumError::io(path, e))?;
+    Ok((size, format!("{:x}", hasher.finalize())))
+}
+
+fn is_empty_dir(dir: &Path) -> Result<bool, ZettariumError> {
+    if !dir.exists() {
+        return Ok(true);
+    }
+    let mut entries = fs::read_dir(dir).map_err(|e| ZettariumError::io(dir, e))?;
+    Ok(entries.next().is_none())
+}
+
+// Test
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        EditorConfig, FinderConfig, GitConfig, JournalConfig, LinksConfig, NoteType, PathsConfig,
+        TemplatesConfig, create_zettel, store::run_migrations,
+    };
+
+    fn test_config(root: &Path) -> AppConfig {
+        AppConfig {
+            paths: PathsConfig {
+                db_path: root.join("zettarium.db").display().to_string(),
+                zettel_dir: root.join("notes").display().to_string(),
+                archive_dir: root.join("archive").display().to_string(),
+                trash_dir: None,
+            },
+            editor: EditorConfig {
+                editor: "true".into(),
+            },
+            templates: TemplatesConfig::default(),
+            git: GitConfig::default(),
+            finder: FinderConfig::default(),
+            links: LinksConfig::default(),
+            journal: JournalConfig::default(),
+            types: HashMap::new(),
+        }
+    }
+
+    #[test]
+    fn test_bundle_round_trip() {
+        let source = WorkDir::new("test-source").unwrap();
+        let target = WorkDir::new("test-target").unwrap();
+        let config = test_config(&source.0);
+
+        let conn = &mut SqliteConnection::establish(&config.paths.db_path).unwrap();
+        run_migrations(conn).unwrap();
+        let zettel = create_zettel(conn, "Bundled", &NoteType::Permanent, &[]).unwrap();
+        fs::create_dir_all(&config.paths.zettel_dir).unwrap();
+        fs::write(
+            Path::new(&config.paths.zettel_dir).join(format!("{}.md", zettel.id)),
+            "body",
+        )
+        .unwrap();
+
+        let bundle = source.0.join("vault.tar.zst");
+        let manifest = create_bundle(conn, &config, &bundle).unwrap();
+        assert_eq!(manifest.note_count, 1);
+        assert_eq!(manifest.files.len(), 2);
+
+        let restored = test_config(&target.0);
+        restore_bundle(&restored, &bundle, false).unwrap();
+        let note = Path::new(&restored.paths.zettel_dir).join(format!("{}.md", zettel.id));
+        assert_eq!(fs::read_to_string(note).unwrap(), "body");
+
+        // 既存のデータは --force なしでは上書きしない
+        let err = restore_bundle(&restored, &bundle, false).unwrap_err();
+        assert_eq!(err.exit_code(), 20);
+        restore_bundle(&restored, &bundle, true).unwrap();
+    }
+
+    #[test]
+    fn test_bundle_trash_and_git() {
+        let source = WorkDir::new("test-source").unwrap();
+        let target = WorkDir::new("test-target").unwrap();
+        let mut config = test_config(&source.0);
+        config.paths.trash_dir = Some(source.0.join("trash").display().to_string());
+
+        let conn = &mut SqliteConnection::establish(&config.paths.db_path).unwrap();
+        run_migrations(conn).unwrap();
+        let notes = Path::new(&config.paths.zettel_dir);
+        fs::create_dir_all(notes.join(GIT_DIR)).unwrap();
+        fs::write(notes.join(GIT_DIR).join("HEAD"), "ref").unwrap();
+        fs::create_dir_all(config.paths.trash_dir()).unwrap();
+        fs::write(Path::new(&config.paths.trash_dir()).join("old.md"), "old").unwrap();
+
+        let bundle = source.0.join("vault.tar.zst");
+        let manifest = create_bundle(conn, &config, &bundle).unwrap();
+        let paths: Vec<&str> = manifest.files.iter().map(|f| f.path.as_str()).collect();
+        assert_eq!(paths, vec![DB_ENTRY, "trash/old.md"]);
+
+        // 既存のgitリポジトリは --force でも残す
+        let mut restored = test_config(&target.0);
+        restored.paths.trash_dir = Some(target.0.join("trash").display().to_string());
+        let target_git = Path::new(&restored.paths.zettel_dir).join(GIT_DIR);
+        fs::create_dir_all(&target_git).unwrap();
+        restore_bundle(&restored, &bundle, true).unwrap();
+        assert!(target_git.exists());
+        assert!(
+            Path::new(&restored.paths.trash_dir())
+                .join("old.md")
+                .exists()
+        );
+    }
+}
@@ -1,17 +1,58 @@
-use crate::AppConfig;
-use anyhow::Result;
+use crate::{AppConfig, ZettariumError};
+use diesel::migration::MigrationSource;
 use diesel::prelude::*;
+use diesel::sql_query;
+use diesel::sqlite::Sqlite;
 use diesel_migrations::{EmbeddedMigrations, MigrationHarness, embed_migrations};
+use std::path::Path;
 
 pub const MIGRATIONS: EmbeddedMigrations = embed_migrations!();
 
-pub fn establish_connection(config: &AppConfig) -> SqliteConnection {
+pub fn establish_connection(config: &AppConfig) -> Result<SqliteConnection, ZettariumError> {
     let database_url = &config.paths.db_path;
-    SqliteConnection::establish(&database_url)
-        .unwrap_or_else(|_| panic!("Error connecting to {}", database_url))
+    let mut conn = SqliteConnection::establish(database_url).map_err(|e| {
+        ZettariumError::DatabaseUnavailable {
+            path: database_url.clone(),
+            message: e.to_string(),
+        }
+    })?;
+
+    // 既存のDBにも新しいテーブルを追加する
+    run_migrations(&mut conn)?;
+    Ok(conn)
+}
+
+pub fn run_migrations(conn: &mut SqliteConnection) -> Result<(), ZettariumError> {
+    conn.run_pending_migrations(MIGRATIONS)
+        .map_err(|e| ZettariumError::Migration(e.to_string()))?;
+    Ok(())
+}
+
+// 適用済みのマイグレーションのうち最新のもの
+pub fn schema_version(conn: &mut SqliteConnection) -> Result<String, ZettariumError> {
+    let applied = conn
+        .applied_migrations()
+        .map_err(|e| ZettariumError::Migration(e.to_string()))?;
+    Ok(applied
+        .iter()
+        .map(|v| v.to_string())
+        .max()
+        .unwrap_or_default())
+}
+
+// このバイナリに含まれるマイグレーションのバージョン一覧
+pub fn known_schema_versions() -> Result<Vec<String>, ZettariumError> {
+    let migrations = MigrationSource::<Sqlite>::migrations(&MIGRATIONS)
+        .map_err(|e| ZettariumError::Migration(e.to_string()))?;
+    Ok(migrations
+        .iter()
+        .map(|m| m.name().version().to_string())
+        .collect())
 }
 
-pub fn run_migrations(conn: &mut SqliteConnection) -> Result<()> {
-    let _ = conn.run_pending_migrations(MIGRATIONS);
+// 書き込み中でも整合性の取れたコピーを作る
+pub fn snapshot_database(conn: &mut SqliteConnection, to: &Path) -> Result<(), ZettariumError> {
+    let target = to.display().to_string().replace('\'', "''");
+    sql_query(format!("VACUUM INTO '{}'", target)).execute(conn)?;
     Ok(())
 }
@@ -0,0 +1,96 @@
+use crate::{
+    Revision, ZettariumError,
+    schema::revisions::{self, dsl::*},
+};
+use chrono::{Local, NaiveDateTime};
+use diesel::{SqliteConnection, prelude::*};
+
+#[derive(Debug, Insertable)]
+#[diesel(table_name = revisions)]
+pub struct NewRevision {
+    pub zettel_id: String,
+    pub revision: i32,
+    pub front_matter: String,
+    pub body: String,
+    pub created_at: NaiveDateTime,
+}
+
+// 直前のリビジョンと同じ内容なら保存しない
+pub fn create_revision(
+    conn: &mut SqliteConnection,
+    note_id: &str,
+    front_matter_: &str,
+    body_: &str,
+) -> Result<Option<Revision>, ZettariumError> {
+    let latest = get_latest_revision(conn, note_id)?;
+    if let Some(latest) = &latest
+        && latest.front_matter == front_matter_
+        && latest.body == body_
+    {
+        return Ok(None);
+    }
+
+    let new_revision = NewRevision {
+        zettel_id: note_id.to_string(),
+        revision: latest.map_or(1, |r| r.revision + 1),
+        front_matter: front_matter_.to_string(),
+        body: body_.to_string(),
+        created_at: Local::now().naive_local(),
+    };
+    let created = diesel::insert_into(revisions::table)
+        .values(&new_revision)
+        .returning(Revision::as_select())
+        .get_result(conn)?;
+
+    Ok(Some(created))
+}
+
+pub fn list_revisions(
+    conn: &mut SqliteConnection,
+    note_id: &str,
+) -> Result<Vec<Revision>, ZettariumError> {
+    let results = revisions
+        .filter(zettel_id.eq(note_id))
+        .order(revision.asc())
+        .select(Revision::as_select())
+        .load(conn)?;
+
+    Ok(results)
+}
+
+pub fn get_revision(
+    conn: &mut SqliteConnection,
+    note_id: &str,
+    number: i32,
+) -> Result<Option<Revision>, ZettariumError> {
+    let result = revisions
+        .filter(zettel_id.eq(note_id))
+        .filter(revision.eq(number))
+        .select(Revision::as_select())
+        .first(conn)
+        .optional()?;
+
+    Ok(result)
+}
+
+pub fn get_latest_revision(
+    conn: &mut SqliteConnection,
+    note_id: &str,
+) -> Result<Option<Revision>, ZettariumError> {
+    let result = revisions
+        .filter(zettel_id.eq(note_id))
+        .order(revision.desc())
+        .select(Revision::as_select())
+        .first(conn)
+        .optional()?;
+
+    Ok(result)
+}
+
+pub fn delete_revisions_by_zettel_id(
+    conn: &mut SqliteConnection,
+    note_id: &str,
+) -> Result<usize, ZettariumError> {
+    let count = diesel::delete(revisions.filter(zettel_id.eq(note_id))).execute(conn)?;
+    Ok(count)
+}
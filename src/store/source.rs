@@ -0,0 +1,106 @@
+use crate::{
+    Source, SourceMeta, ZettariumError,
+    schema::sources::{self, dsl::*},
+};
+use diesel::{SqliteConnection, prelude::*};
+
+#[derive(Debug, Insertable, AsChangeset)]
+#[diesel(table_name = sources)]
+#[diesel(treat_none_as_null = true)]
+pub struct NewSource {
+    pub citekey: String,
+    pub zettel_id: Option<String>,
+    pub title: String,
+    pub authors: String,
+    pub year: Option<i32>,
+    pub publisher: Option<String>,
+    pub url: Option<String>,
+    pub doi: Option<String>,
+}
+
+impl NewSource {
+    // front matterの書誌情報からレコードを作る（タイトルがなければノートのタイトルを使う）
+    pub fn from_meta(meta: &SourceMeta, note_id: &str, note_title: &str) -> Self {
+        NewSource {
+            citekey: meta.citekey.clone(),
+            zettel_id: Some(note_id.to_string()),
+            title: meta.title.clone().unwrap_or_else(|| note_title.to_string()),
+            authors: meta.authors.join(" and "),
+            year: meta.year,
+            publisher: meta.publisher.clone(),
+            url: meta.url.clone(),
+            doi: meta.doi.clone(),
+        }
+    }
+}
+
+pub fn create_source(
+    conn: &mut SqliteConnection,
+    new_source: &NewSource,
+) -> Result<Source, ZettariumError> {
+    let source = diesel::insert_into(sources::table)
+        .values(new_source)
+        .returning(Source::as_select())
+        .get_result(conn)?;
+
+    Ok(source)
+}
+
+// citekeyが既にあれば書誌情報を更新する
+pub fn upsert_source(
+    conn: &mut SqliteConnection,
+    new_source: &NewSource,
+) -> Result<Source, ZettariumError> {
+    let source = diesel::insert_into(sources::table)
+        .values(new_source)
+        .on_conflict(citekey)
+        .do_update()
+        .set(new_source)
+        .returning(Source::as_select())
+        .get_result(conn)?;
+
+    Ok(source)
+}
+
+pub fn get_source(
+    conn: &mut SqliteConnection,
+    key: &str,
+) -> Result<Option<Source>, ZettariumError> {
+    let source = sources
+        .find(key)
+        .select(Source::as_select())
+        .first(conn)
+        .optional()?;
+
+    Ok(source)
+}
+
+pub fn get_source_by_zettel_id(
+    conn: &mut SqliteConnection,
+    id: &str,
+) -> Result<Option<Source>, ZettariumError> {
+    let source = sources
+        .filter(zettel_id.eq(id))
+        .select(Source::as_select())
+        .first(conn)
+        .optional()?;
+
+    Ok(source)
+}
+
+pub fn list_sources(conn: &mut SqliteConnection) -> Result<Vec<Source>, ZettariumError> {
+    let results = sources
+        .order((year.desc(), citekey.asc()))
+        .select(Source::as_select())
+        .load(conn)?;
+
+    Ok(results)
+}
+
+pub fn delete_sources_by_zettel_id(
+    conn: &mut SqliteConnection,
+    id: &str,
+) -> Result<usize, ZettariumError> {
+    let count = diesel::delete(sources.filter(zettel_id.eq(id))).execute(conn)?;
+    Ok(count)
+}
@@ -1,8 +1,7 @@
 use crate::{
-    Tag,
+    Tag, ZettariumError,
     schema::{tags, tags::dsl::*},
 };
-use anyhow::{Error, Result};
 use diesel::{SqliteConnection, prelude::*};
 
 #[derive(Insertable)]
@@ -12,7 +11,7 @@ pub struct NewTag {
     pub tag_name: String,
 }
 
-pub fn create_tag(conn: &mut SqliteConnection, tag_str: &str) -> Result<Tag, Error> {
+pub fn create_tag(conn: &mut SqliteConnection, tag_str: &str) -> Result<Tag, ZettariumError> {
     // Tag構造体にマッピング
     let new_tag = NewTag {
         id: generate_tag_id(conn)?,
@@ -28,7 +27,7 @@ pub fn create_tag(conn: &mut SqliteConnection, tag_str: &str) -> Result<Tag, Err
     Ok(tag)
 }
 
-pub fn get_tag_name(conn: &mut SqliteConnection, tag: &str) -> Result<Option<Tag>, Error> {
+pub fn get_tag_name(conn: &mut SqliteConnection, tag: &str) -> Result<Option<Tag>, ZettariumError> {
     let tag = tags
         .filter(tag_name.eq(tag))
         .select(Tag::as_select())
@@ -37,7 +36,7 @@ pub fn get_tag_name(conn: &mut SqliteConnection, tag: &str) -> Result<Option<Tag
     Ok(tag)
 }
 
-fn generate_tag_id(conn: &mut SqliteConnection) -> Result<String, Error> {
+fn generate_tag_id(conn: &mut SqliteConnection) -> Result<String, ZettariumError> {
     use regex::Regex;
 
     let all_ids: Vec<String> = tags.select(id).load::<String>(conn)?;
@@ -1,9 +1,10 @@
 use crate::{
-    NoteType, Zettel, create_tag, create_zettel_tag, exists_zettel_tag, get_tag_name,
+    FrontMatter, NewSource, NoteType, ZettariumError, Zettel, create_tag, create_zettel_tag,
+    delete_attachments_by_zettel_id, delete_revisions_by_zettel_id, delete_sources_by_zettel_id,
+    delete_zettel_tags_by_zettel_id, exists_zettel_tag, get_tag_name,
     schema::zettels::{self, dsl::*},
-    tags, zettel_tags,
+    tags, upsert_source, zettel_tags,
 };
-use anyhow::{Error, Result, anyhow};
 use chrono::{Local, NaiveDateTime};
 use diesel::{SqliteConnection, prelude::*};
 use serde::Serialize;
@@ -17,6 +18,8 @@ pub struct NewZettel {
     pub created_at: NaiveDateTime,
     pub updated_at: NaiveDateTime,
     pub archived: bool,
+    pub deleted_at: Option<NaiveDateTime>,
+    pub processed_at: Option<NaiveDateTime>,
 }
 
 #[derive(AsChangeset)]
@@ -33,22 +36,47 @@ pub struct ArchivedZettel {
     pub archived: bool,
 }
 
+#[derive(AsChangeset)]
+#[diesel(table_name = zettels, treat_none_as_null = true)]
+pub struct TrashedZettel {
+    pub deleted_at: Option<NaiveDateTime>,
+}
+
 pub fn create_zettel(
     conn: &mut SqliteConnection,
     title_: &str,
-    note_type: &str,
+    note_type: &NoteType,
+    tags_name: &[String],
+) -> Result<Zettel, ZettariumError> {
+    create_zettel_at(
+        conn,
+        title_,
+        note_type,
+        tags_name,
+        Local::now().naive_local(),
+    )
+}
+
+// 作成日時を指定してZettelを作成する（import時に元ノートの作成日時を引き継ぐ）
+pub fn create_zettel_at(
+    conn: &mut SqliteConnection,
+    title_: &str,
+    note_type: &NoteType,
     tags_name: &[String],
-) -> Result<Zettel, Error> {
-    conn.transaction::<Zettel, Error, _>(|conn| {
+    created: NaiveDateTime,
+) -> Result<Zettel, ZettariumError> {
+    conn.transaction::<Zettel, ZettariumError, _>(|conn| {
         // このクロージャ内で複数のDB操作を行う
         // Zettel構造体にマッピング
         let new_zettel = NewZettel {
-            id: generate_zettel_id(),
+            id: generate_zettel_id(conn, created)?,
             title: title_.to_string(),
-            type_: note_type.parse::<NoteType>()?,
-            created_at: Local::now().naive_local(),
+            type_: note_type.clone(),
+            created_at: created,
             updated_at: Local::now().naive_local(),
             archived: false,
+            deleted_at: None,
+            processed_at: None,
         };
 
         // SQLiteに保存する処理
@@ -81,12 +109,13 @@ pub fn list_zettels(
     tags_name: &[String],
     all: bool,
     archived_only: bool,
-) -> Result<Vec<Zettel>, Error> {
+) -> Result<Vec<Zettel>, ZettariumError> {
+    // ゴミ箱のノートはどの一覧にも出さない
     if all {
-        return Ok(zettels.load::<Zettel>(conn)?);
+        return Ok(zettels.filter(deleted_at.is_null()).load::<Zettel>(conn)?);
     }
 
-    let mut query = zettels.into_boxed();
+    let mut query = zettels.filter(deleted_at.is_null()).into_boxed();
 
     if let Some(id_filter) = zettel_id {
         query = query.filter(id.eq(id_filter));
@@ -108,6 +137,7 @@ pub fn list_zettels(
             .inner_join(tags::table.on(tags::id.eq(zettel_tags::tag_id)))
             .filter(tags::tag_name.eq_any(tags_name))
             .filter(archived.eq(archived_only))
+            .filter(deleted_at.is_null())
             .select(Zettel::as_select())
             .distinct();
 
@@ -121,12 +151,12 @@ pub fn update_zettel(
     conn: &mut SqliteConnection,
     zettel_id: &str,
     title_: &str,
-    note_type: &str,
+    note_type: &NoteType,
     tags_name: &[String],
-) -> Result<Zettel, Error> {
+) -> Result<Zettel, ZettariumError> {
     let inserted_zettel = UpdatedZettel {
         title: title_.to_string(),
-        type_: note_type.parse::<NoteType>()?,
+        type_: note_type.clone(),
         updated_at: Local::now().naive_local(),
     };
 
@@ -153,13 +183,14 @@ pub fn update_zettel(
     Ok(updated_zettel)
 }
 
-pub fn archive_zettel(conn: &mut SqliteConnection, zettel_id: &str) -> Result<Zettel, Error> {
+pub fn archive_zettel(
+    conn: &mut SqliteConnection,
+    zettel_id: &str,
+) -> Result<Zettel, ZettariumError> {
     let exist_zettel = ensure_zettel_exists(conn, zettel_id)?;
 
     if exist_zettel.archived {
-        if exist_zettel.archived {
-            return Err(anyhow!("Note is already archived"));
-        }
+        return Err(ZettariumError::AlreadyArchived(exist_zettel.id));
     }
 
     let archived_zettel = diesel::update(zettels.find(zettel_id))
@@ -170,38 +201,145 @@ pub fn archive_zettel(conn: &mut SqliteConnection, zettel_id: &str) -> Result<Ze
     Ok(archived_zettel)
 }
 
-pub fn remove_zettel(conn: &mut SqliteConnection, zettel_id: &str) -> Result<usize> {
+pub fn mark_zettel_processed(
+    conn: &mut SqliteConnection,
+    zettel_id: &str,
+) -> Result<Zettel, ZettariumError> {
+    ensure_zettel_exists(conn, zettel_id)?;
+
+    let processed = diesel::update(zettels.find(zettel_id))
+        .set(processed_at.eq(Some(Local::now().naive_local())))
+        .returning(Zettel::as_select())
+        .get_result(conn)?;
+
+    Ok(processed)
+}
+
+// 未整理のFleetingノートを古い順に返す
+pub fn list_unprocessed_zettels(
+    conn: &mut SqliteConnection,
+) -> Result<Vec<Zettel>, ZettariumError> {
+    Ok(zettels
+        .filter(type_.eq(NoteType::Fleeting))
+        .filter(archived.eq(false))
+        .filter(deleted_at.is_null())
+        .filter(processed_at.is_null())
+        .order((created_at.asc(), id.asc()))
+        .load::<Zettel>(conn)?)
+}
+
+pub fn remove_zettel(
+    conn: &mut SqliteConnection,
+    zettel_id: &str,
+) -> Result<usize, ZettariumError> {
+    // 書誌情報はノートのfront matterと対になっているので一緒に消す
+    delete_sources_by_zettel_id(conn, zettel_id)?;
+    delete_revisions_by_zettel_id(conn, zettel_id)?;
+    delete_attachments_by_zettel_id(conn, zettel_id)?;
+    // 同じIDでノートを作り直せるようにタグの紐付けも消す
+    delete_zettel_tags_by_zettel_id(conn, zettel_id)?;
     let count = diesel::delete(zettels.find(zettel_id)).execute(conn)?;
     Ok(count)
 }
 
+pub fn count_zettels(conn: &mut SqliteConnection) -> Result<i64, ZettariumError> {
+    Ok(zettels.count().get_result(conn)?)
+}
+
 pub fn find_zettel_by_title(
     conn: &mut SqliteConnection,
     keyword: &str,
-) -> Result<Vec<Zettel>, Error> {
+) -> Result<Vec<Zettel>, ZettariumError> {
     let pattern = format!("%{}%", keyword); // 部分一致検索
-    let results = zettels.filter(title.like(&pattern)).load::<Zettel>(conn)?;
+    let results = zettels
+        .filter(title.like(&pattern))
+        .filter(deleted_at.is_null())
+        .load::<Zettel>(conn)?;
 
     Ok(results)
 }
 
-pub fn ensure_zettel_exists(conn: &mut SqliteConnection, zettel_id: &str) -> Result<Zettel, Error> {
+pub fn ensure_zettel_exists(
+    conn: &mut SqliteConnection,
+    zettel_id: &str,
+) -> Result<Zettel, ZettariumError> {
+    // ゴミ箱のノートは存在しないものとして扱う
     let zettel = zettels
         .find(zettel_id)
+        .filter(deleted_at.is_null())
         .select(Zettel::as_select())
         .first(conn)
         .optional()?;
 
     match zettel {
         Some(existing) => Ok(existing),
-        None => Err(diesel::result::Error::NotFound.into()),
+        None => Err(ZettariumError::NoteNotFound(zettel_id.to_string())),
     }
 }
 
+pub fn trash_zettel(
+    conn: &mut SqliteConnection,
+    zettel_id: &str,
+) -> Result<Zettel, ZettariumError> {
+    ensure_zettel_exists(conn, zettel_id)?;
+
+    let trashed = diesel::update(zettels.find(zettel_id))
+        .set(TrashedZettel {
+            deleted_at: Some(Local::now().naive_local()),
+        })
+        .returning(Zettel::as_select())
+        .get_result(conn)?;
+
+    Ok(trashed)
+}
+
+pub fn restore_trashed_zettel(
+    conn: &mut SqliteConnection,
+    zettel_id: &str,
+) -> Result<Zettel, ZettariumError> {
+    get_trashed_zettel(conn, zettel_id)?;
+
+    let restored = diesel::update(zettels.find(zettel_id))
+        .set(TrashedZettel { deleted_at: None })
+        .returning(Zettel::as_select())
+        .get_result(conn)?;
+
+    Ok(restored)
+}
+
+pub fn get_trashed_zettel(
+    conn: &mut SqliteConnection,
+    zettel_id: &str,
+) -> Result<Zettel, ZettariumError> {
+    zettels
+        .find(zettel_id)
+        .filter(deleted_at.is_not_null())
+        .select(Zettel::as_select())
+        .first(conn)
+        .optional()?
+        .ok_or_else(|| ZettariumError::NoteNotFound(format!("{} (in trash)", zettel_id)))
+}
+
+// ゴミ箱のノートを古い順に返す（beforeを指定するとそれ以前に捨てたものだけ）
+pub fn list_trashed_zettels(
+    conn: &mut SqliteConnection,
+    before: Option<NaiveDateTime>,
+) -> Result<Vec<Zettel>, ZettariumError> {
+    let mut query = zettels.filter(deleted_at.is_not_null()).into_boxed();
+    if let Some(before) = before {
+        query = query.filter(deleted_at.le(before));
+    }
+
+    Ok(query
+        .order(deleted_at.asc())
+        .select(Zettel::as_select())
+        .load(conn)?)
+}
+
 pub fn update_zettel_timestamp_only(
     conn: &mut SqliteConnection,
     zettel_id: &str,
-) -> Result<Zettel> {
+) -> Result<Zettel, ZettariumError> {
     let updated = diesel::update(zettels.find(zettel_id))
         .set(updated_at.eq(Local::now().naive_local()))
         .returning(Zettel::as_select())
@@ -210,6 +348,100 @@ pub fn update_zettel_timestamp_only(
     Ok(updated)
 }
 
-fn generate_zettel_id() -> String {
-    Local::now().format("%Y%m%dT%H%M%S").to_string()
+// Markdownのfront matterからzettels / tags / zettel_tagsを作り直す
+pub fn rebuild_index(
+    conn: &mut SqliteConnection,
+    entries: &[FrontMatter],
+) -> Result<(), ZettariumError> {
+    conn.transaction::<(), ZettariumError, _>(|conn| {
+        // sourcesはfront matterにあるものだけ上書きする（ノートのない書誌情報は残す）
+        diesel::delete(zettel_tags::table).execute(conn)?;
+        diesel::delete(tags::table).execute(conn)?;
+        diesel::delete(zettels::table).execute(conn)?;
+
+        for entry in entries {
+            let zettel = entry.get_zettel();
+            let new_zettel = NewZettel {
+                id: zettel.id.clone(),
+                title: zettel.title.clone(),
+                type_: zettel.type_.clone(),
+                created_at: zettel.created_at,
+                updated_at: zettel.updated_at,
+                archived: zettel.archived,
+                deleted_at: zettel.deleted_at,
+                processed_at: zettel.processed_at,
+            };
+            diesel::insert_into(zettels::table)
+                .values(&new_zettel)
+                .execute(conn)?;
+
+            for name in entry.get_tags() {
+                let tag = match get_tag_name(conn, name)? {
+                    Some(existing) => existing,
+                    None => create_tag(conn, name)?,
+                };
+                if !exists_zettel_tag(conn, &zettel.id, &tag.id)? {
+                    create_zettel_tag(conn, &zettel.id, &tag.id)?;
+                }
+            }
+
+            if let Some(meta) = entry.get_source() {
+                upsert_source(conn, &NewSource::from_meta(meta, &zettel.id, &zettel.title))?;
+            }
+        }
+        Ok(())
+    })
+}
+
+// 同じ秒に複数作成された場合は1秒ずつずらして重複を避ける
+pub fn generate_zettel_id(
+    conn: &mut SqliteConnection,
+    created: NaiveDateTime,
+) -> Result<String, ZettariumError> {
+    let mut candidate = created;
+    loop {
+        let new_id = candidate.format("%Y%m%dT%H%M%S").to_string();
+        let count = zettels
+            .filter(id.eq(&new_id))
+            .count()
+            .get_result::<i64>(conn)?;
+        if count == 0 {
+            return Ok(new_id);
+        }
+        candidate += chrono::Duration::seconds(1);
+    }
+}
+
+// Test
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::store::run_migrations;
+
+    #[test]
+    fn test_remove_tagged_zettel_and_recreate() {
+        let conn = &mut SqliteConnection::establish(":memory:").unwrap();
+        run_migrations(conn).unwrap();
+        let created = chrono::NaiveDate::from_ymd_opt(2025, 6, 10)
+            .unwrap()
+            .and_hms_opt(0, 0, 0)
+            .unwrap();
+        let tags_ = vec!["journal".to_string()];
+
+        let zettel =
+            create_zettel_at(conn, "2025-06-10", &NoteType::Fleeting, &tags_, created).unwrap();
+        trash_zettel(conn, &zettel.id).unwrap();
+        assert_eq!(remove_zettel(conn, &zettel.id).unwrap(), 1);
+
+        // 消したノートと同じIDで作り直せる
+        let recreated =
+            create_zettel_at(conn, "2025-06-10", &NoteType::Fleeting, &tags_, created).unwrap();
+        assert_eq!(recreated.id, zettel.id);
+        assert_eq!(
+            crate::get_tag_by_zettel_id(conn, &recreated.id)
+                .unwrap()
+                .len(),
+            1
+        );
+    }
 }
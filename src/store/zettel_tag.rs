@@ -1,7 +1,5 @@
-use anyhow::{Error, Result};
-
 use crate::{
-    Tag, ZettelTag,
+    Tag, ZettariumError, ZettelTag,
     schema::{tags, zettel_tags, zettel_tags::dsl::*},
 };
 use diesel::{SqliteConnection, prelude::*};
@@ -17,7 +15,7 @@ pub fn create_zettel_tag(
     conn: &mut SqliteConnection,
     z_id: &str,
     t_id: &str,
-) -> Result<ZettelTag, Error> {
+) -> Result<ZettelTag, ZettariumError> {
     // ZettelTag構造体にマッピング
     let new_zettel_tag = NewZettelTag {
         zettel_id: z_id.to_string(),
@@ -33,7 +31,33 @@ pub fn create_zettel_tag(
     Ok(zettel_tag)
 }
 
-pub fn get_tag_by_zettel_id(conn: &mut SqliteConnection, id: &str) -> Result<Vec<Tag>, Error> {
+pub fn delete_zettel_tag(
+    conn: &mut SqliteConnection,
+    zettel_id_: &str,
+    tag_id_: &str,
+) -> Result<usize, ZettariumError> {
+    let count = diesel::delete(
+        zettel_tags
+            .filter(zettel_id.eq(zettel_id_))
+            .filter(tag_id.eq(tag_id_)),
+    )
+    .execute(conn)?;
+
+    Ok(count)
+}
+
+pub fn delete_zettel_tags_by_zettel_id(
+    conn: &mut SqliteConnection,
+    zettel_id_: &str,
+) -> Result<usize, ZettariumError> {
+    let count = diesel::delete(zettel_tags.filter(zettel_id.eq(zettel_id_))).execute(conn)?;
+    Ok(count)
+}
+
+pub fn get_tag_by_zettel_id(
+    conn: &mut SqliteConnection,
+    id: &str,
+) -> Result<Vec<Tag>, ZettariumError> {
     let tags = zettel_tags::table
         .inner_join(tags::table.on(zettel_tags::tag_id.eq(tags::id)))
         .filter(zettel_tags::zettel_id.eq(id))
@@ -47,7 +71,7 @@ pub fn exists_zettel_tag(
     conn: &mut SqliteConnection,
     zettel_id_: &str,
     tag_id_: &str,
-) -> Result<bool, Error> {
+) -> Result<bool, ZettariumError> {
     let count = zettel_tags
         .filter(zettel_id.eq(zettel_id_))
         .filter(tag_id.eq(tag_id_))
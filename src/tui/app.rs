@@ -0,0 +1,315 @@
+use termimad::crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+
+use crate::{LinkFormat, Zettel, extract_note_links, score_fields};
+
+// 一覧に表示するノート（本文はプレビューとリンクの計算に使う）
+#[derive(Debug, Clone)]
+pub struct TuiNote {
+    pub zettel: Zettel,
+    pub tags: Vec<String>,
+    pub body: String,
+    // 本文中のリンク先ID（描画のたびに本文を解析しないよう先に求めておく）
+    pub links: Vec<String>,
+}
+
+impl TuiNote {
+    pub fn new(zettel: Zettel, tags: Vec<String>, body: String) -> Self {
+        let links = extract_note_links(&body);
+        TuiNote {
+            zettel,
+            tags,
+            body,
+            links,
+        }
+    }
+
+    pub fn link(&self, format: LinkFormat) -> String {
+        format.format(&self.zettel.id, &self.zettel.title)
+    }
+
+    fn score(&self, query: &str) -> Option<i64> {
+        let tags: Vec<String> = self.tags.iter().map(|t| format!("#{}", t)).collect();
+        let mut fields = vec![self.zettel.id.as_str(), self.zettel.title.as_str()];
+        fields.extend(tags.iter().map(String::as_str));
+        score_fields(query, &fields)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TuiMode {
+    Normal,
+    Filter,
+    Tag(String),
+    ConfirmArchive,
+}
+
+// 画面の外（DBやエディタ）で行う操作
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TuiAction {
+    Quit,
+    Edit(String),
+    Archive(String),
+    AddTags(String, Vec<String>),
+    CopyLink(String),
+}
+
+pub struct TuiApp {
+    pub notes: Vec<TuiNote>,
+    pub filter: String,
+    // notesのうちフィルタに一致するもののindex
+    pub visible: Vec<usize>,
+    pub selected: usize,
+    pub mode: TuiMode,
+    pub preview_scroll: usize,
+    pub message: Option<String>,
+}
+
+impl TuiApp {
+    pub fn new(notes: Vec<TuiNote>) -> Self {
+        let mut app = TuiApp {
+            notes,
+            filter: String::new(),
+            visible: vec![],
+            selected: 0,
+            mode: TuiMode::Normal,
+            preview_scroll: 0,
+            message: None,
+        };
+        app.apply_filter();
+        app
+    }
+
+    // 操作後にノートを読み直す（選択中のノートはIDで引き継ぐ）
+    pub fn reload(&mut self, notes: Vec<TuiNote>) {
+        let current = self.selected_note().map(|n| n.zettel.id.clone());
+        self.notes = notes;
+        self.apply_filter();
+        if let Some(id) = current
+            && let Some(pos) = self
+                .visible
+                .iter()
+                .position(|&i| self.notes[i].zettel.id == id)
+        {
+            self.selected = pos;
+        }
+    }
+
+    // `z find --top` と同じfuzzy matcherで絞り込み、スコア順に並べる
+    pub fn apply_filter(&mut self) {
+        let mut ranked: Vec<(i64, usize)> = self
+            .notes
+            .iter()
+            .enumerate()
+            .filter_map(|(i, note)| note.score(&self.filter).map(|score| (score, i)))
+            .collect();
+        // 同点（フィルタなしを含む）は元の並び順のまま
+        ranked.sort_by_key(|&(score, i)| (std::cmp::Reverse(score), i));
+        self.visible = ranked.into_iter().map(|(_, i)| i).collect();
+        self.selected = self.selected.min(self.visible.len().saturating_sub(1));
+        self.preview_scroll = 0;
+    }
+
+    pub fn selected_note(&self) -> Option<&TuiNote> {
+        self.visible.get(self.selected).map(|&i| &self.notes[i])
+    }
+
+    // 選択中のノートへリンクしているノート
+    pub fn backlinks(&self) -> Vec<&TuiNote> {
+        let Some(target) = self.selected_note() else {
+            return vec![];
+        };
+        self.notes
+            .iter()
+            .filter(|n| n.zettel.id != target.zettel.id && n.links.contains(&target.zettel.id))
+            .collect()
+    }
+
+    // 選択中のノートからのリンク（一覧にないノートはIDだけ返す）
+    pub fn outgoing(&self) -> Vec<(String, Option<&TuiNote>)> {
+        let Some(note) = self.selected_note() else {
+            return vec![];
+        };
+        note.links
+            .iter()
+            .map(|id| {
+                let linked = self.notes.iter().find(|n| &n.zettel.id == id);
+                (id.clone(), linked)
+            })
+            .collect()
+    }
+
+    fn select(&mut self, index: usize) {
+        let index = index.min(self.visible.len().saturating_sub(1));
+        if index != self.selected {
+            self.selected = index;
+            self.preview_scroll = 0;
+        }
+    }
+
+    pub fn handle_key(&mut self, key: KeyEvent) -> Option<TuiAction> {
+        if key.modifiers.contains(KeyModifiers::CONTROL) && key.code == KeyCode::Char('c') {
+            return Some(TuiAction::Quit);
+        }
+        self.message = None;
+
+        match self.mode.clone() {
+            TuiMode::Normal => self.handle_normal_key(key),
+            TuiMode::Filter => {
+                match key.code {
+                    KeyCode::Enter | KeyCode::Esc => self.mode = TuiMode::Normal,
+                    KeyCode::Backspace => {
+                        self.filter.pop();
+                        self.apply_filter();
+                    }
+                    KeyCode::Up => self.select(self.selected.saturating_sub(1)),
+                    KeyCode::Down => self.select(self.selected + 1),
+                    KeyCode::Char(c) => {
+                        self.filter.push(c);
+                        self.apply_filter();
+                    }
+                    _ => {}
+                }
+                None
+            }
+            TuiMode::Tag(mut input) => match key.code {
+                KeyCode::Esc => {
+                    self.mode = TuiMode::Normal;
+                    None
+                }
+                KeyCode::Enter => {
+                    self.mode = TuiMode::Normal;
+                    // 重複は警告を出さずにここで除く（画面が崩れないように）
+                    let mut tags: Vec<String> = vec![];
+                    for tag in input.split([',', ' ']).map(str::trim) {
+                        if !tag.is_empty() && !tags.iter().any(|t| t.eq_ignore_ascii_case(tag)) {
+                            tags.push(tag.to_string());
+                        }
+                    }
+                    let note = self.selected_note()?;
+                    if tags.is_empty() {
+                        return None;
+                    }
+                    Some(TuiAction::AddTags(note.zettel.id.clone(), tags))
+                }
+                KeyCode::Backspace => {
+                    input.pop();
+                    self.mode = TuiMode::Tag(input);
+                    None
+                }
+                KeyCode::Char(c) => {
+                    input.push(c);
+                    self.mode = TuiMode::Tag(input);
+                    None
+                }
+                _ => None,
+            },
+            TuiMode::ConfirmArchive => {
+                self.mode = TuiMode::Normal;
+                match key.code {
+                    KeyCode::Char('y') | KeyCode::Char('Y') => self
+                        .selected_note()
+                        .map(|n| TuiAction::Archive(n.zettel.id.clone())),
+                    _ => {
+                        self.message = Some("Cancelled.".into());
+                        None
+                    }
+                }
+            }
+        }
+    }
+
+    fn handle_normal_key(&mut self, key: KeyEvent) -> Option<TuiAction> {
+        let selected_id = self.selected_note().map(|n| n.zettel.id.clone());
+        match key.code {
+            KeyCode::Char('q') | KeyCode::Esc => return Some(TuiAction::Quit),
+            KeyCode::Char('j') | KeyCode::Down => self.select(self.selected + 1),
+            KeyCode::Char('k') | KeyCode::Up => self.select(self.selected.saturating_sub(1)),
+            KeyCode::Char('g') | KeyCode::Home => self.select(0),
+            KeyCode::Char('G') | KeyCode::End => self.select(usize::MAX),
+            KeyCode::Char('J') | KeyCode::PageDown => self.preview_scroll += 10,
+            KeyCode::Char('K') | KeyCode::PageUp => {
+                self.preview_scroll = self.preview_scroll.saturating_sub(10)
+            }
+            KeyCode::Char('/') => self.mode = TuiMode::Filter,
+            KeyCode::Enter | KeyCode::Char('e') => return selected_id.map(TuiAction::Edit),
+            KeyCode::Char('a') if selected_id.is_some() => self.mode = TuiMode::ConfirmArchive,
+            KeyCode::Char('t') if selected_id.is_some() => self.mode = TuiMode::Tag(String::new()),
+            KeyCode::Char('y') => return selected_id.map(TuiAction::CopyLink),
+            _ => {}
+        }
+        None
+    }
+}
+
+// Test
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::NoteType;
+    use chrono::NaiveDateTime;
+
+    fn note(id: &str, title: &str, tags: &[&str], body: &str) -> TuiNote {
+        TuiNote::new(
+            Zettel {
+                id: id.into(),
+                title: title.into(),
+                type_: NoteType::Permanent,
+                created_at: NaiveDateTime::default(),
+                updated_at: NaiveDateTime::default(),
+                archived: false,
+                deleted_at: None,
+                processed_at: None,
+            },
+            tags.iter().map(|t| t.to_string()).collect(),
+            body.into(),
+        )
+    }
+
+    fn key(code: KeyCode) -> KeyEvent {
+        KeyEvent::new(code, KeyModifiers::NONE)
+    }
+
+    #[test]
+    fn test_filter_and_links() {
+        let mut app = TuiApp::new(vec![
+            note("20250101T000000", "Rust async", &["rust"], ""),
+            note(
+                "20250101T000001",
+                "Tokio runtime",
+                &["rust", "async"],
+                "See [Rust async](./20250101T000000.md)",
+            ),
+            note("20250101T000002", "Gardening", &[], ""),
+        ]);
+        assert_eq!(app.visible.len(), 3);
+
+        app.handle_key(key(KeyCode::Char('/')));
+        for c in "#rust".chars() {
+            app.handle_key(key(KeyCode::Char(c)));
+        }
+        assert_eq!(app.visible, vec![0, 1]);
+        app.handle_key(key(KeyCode::Enter));
+        assert_eq!(app.mode, TuiMode::Normal);
+
+        let backlinks = app.backlinks();
+        assert_eq!(backlinks.len(), 1);
+        assert_eq!(backlinks[0].zettel.id, "20250101T000001");
+
+        app.handle_key(key(KeyCode::Char('j')));
+        let outgoing = app.outgoing();
+        assert_eq!(outgoing.len(), 1);
+        assert_eq!(outgoing[0].0, "20250101T000000");
+
+        app.handle_key(key(KeyCode::Char('t')));
+        for c in "tokio, runtime".chars() {
+            app.handle_key(key(KeyCode::Char(c)));
+        }
+        assert_eq!(
+            app.handle_key(key(KeyCode::Enter)),
+            Some(TuiAction::AddTags(
+                "20250101T000001".into(),
+                vec!["tokio".into(), "runtime".into()]
+            ))
+        );
+    }
+}
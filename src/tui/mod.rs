@@ -0,0 +1,7 @@
+pub mod app;
+pub mod terminal;
+pub mod view;
+
+pub use app::*;
+pub use terminal::*;
+pub use view::*;
@@ -0,0 +1,40 @@
+use std::io::{self, stdout};
+use termimad::crossterm::{cursor, execute, terminal};
+
+// TUIの間だけ代替画面・rawモードにする（dropで元に戻す）
+pub struct TerminalGuard {
+    active: bool,
+}
+
+impl TerminalGuard {
+    pub fn enter() -> io::Result<Self> {
+        let mut guard = TerminalGuard { active: false };
+        guard.resume()?;
+        Ok(guard)
+    }
+
+    // エディタを開く間は元の画面に戻す
+    pub fn suspend(&mut self) -> io::Result<()> {
+        if self.active {
+            execute!(stdout(), cursor::Show, terminal::LeaveAlternateScreen)?;
+            terminal::disable_raw_mode()?;
+            self.active = false;
+        }
+        Ok(())
+    }
+
+    pub fn resume(&mut self) -> io::Result<()> {
+        if !self.active {
+            terminal::enable_raw_mode()?;
+            execute!(stdout(), terminal::EnterAlternateScreen, cursor::Hide)?;
+            self.active = true;
+        }
+        Ok(())
+    }
+}
+
+impl Drop for TerminalGuard {
+    fn drop(&mut self) {
+        let _ = self.suspend();
+    }
+}
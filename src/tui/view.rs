@@ -0,0 +1,162 @@
+use anyhow::Result;
+use std::io::Write;
+use termimad::{
+    Area, CompoundStyle, CropWriter, MadSkin, MadView,
+    crossterm::{
+        QueueableCommand,
+        cursor::MoveTo,
+        style::{Attribute, Color},
+        terminal::{self, Clear, ClearType},
+    },
+    rgb,
+};
+
+use crate::{TuiApp, TuiMode};
+
+pub fn draw<W: Write>(w: &mut W, app: &TuiApp, skin: &MadSkin) -> Result<()> {
+    let (width, height) = terminal::size()?;
+    w.queue(Clear(ClearType::All))?;
+    if width < 40 || height < 10 {
+        write_line(
+            w,
+            0,
+            0,
+            width,
+            "Terminal is too small.",
+            &CompoundStyle::default(),
+        )?;
+        w.flush()?;
+        return Ok(());
+    }
+
+    let title = CompoundStyle::with_fg(rgb(255, 215, 0));
+    let dim = CompoundStyle::with_fg(Color::DarkGrey);
+    let normal = CompoundStyle::default();
+    let mut selected = CompoundStyle::with_fg(Color::Black);
+    selected.set_bg(rgb(0, 200, 255));
+    selected.add_attr(Attribute::Bold);
+
+    // ────────────── ヘッダー ──────────────
+    let filter = match app.mode {
+        TuiMode::Filter => format!("/{}▏", app.filter),
+        _ if app.filter.is_empty() => String::new(),
+        _ => format!("/{}", app.filter),
+    };
+    let header = format!(
+        " zettarium  {}/{} notes  {}",
+        app.visible.len(),
+        app.notes.len(),
+        filter
+    );
+    write_line(w, 0, 0, width, &header, &title)?;
+
+    // ────────────── ノート一覧（左） ──────────────
+    let body_top = 1;
+    let body_height = height - 2;
+    let list_width = (width * 2 / 5).max(30).min(width - 20);
+    let first = app
+        .selected
+        .saturating_sub(body_height as usize - 1)
+        .min(app.visible.len().saturating_sub(body_height as usize));
+    for row in 0..body_height {
+        let y = body_top + row;
+        match app.visible.get(first + row as usize) {
+            Some(&i) => {
+                let note = &app.notes[i];
+                let style = if first + row as usize == app.selected {
+                    &selected
+                } else {
+                    &normal
+                };
+                let line = format!(" {}  {}", note.zettel.id, note.zettel.title);
+                write_line(w, 0, y, list_width, &line, style)?;
+            }
+            None => write_line(w, 0, y, list_width, "", &normal)?,
+        }
+        write_line(w, list_width, y, 1, "│", &dim)?;
+    }
+
+    // ────────────── プレビュー（右上） ──────────────
+    let right = list_width + 1;
+    let right_width = width - right;
+    let links_height = (body_height / 3).clamp(4, 12);
+    let preview_height = body_height - links_height - 1;
+    if let Some(note) = app.selected_note() {
+        let tags: Vec<String> = note.tags.iter().map(|t| format!("#{}", t)).collect();
+        let info = format!(" {}  {}", note.zettel.type_, tags.join(" "));
+        write_line(w, right, body_top, right_width, &info, &dim)?;
+
+        let area = Area::new(right + 1, body_top + 1, right_width - 1, preview_height - 1);
+        let mut view = MadView::from(note.body.trim().to_string(), area, skin.clone());
+        view.try_scroll_lines(app.preview_scroll as i32);
+        view.write_on(w)?;
+    } else {
+        write_line(w, right, body_top, right_width, " No notes found.", &dim)?;
+    }
+
+    // ────────────── バックリンク / 発リンク（右下） ──────────────
+    let links_top = body_top + preview_height;
+    write_line(
+        w,
+        right,
+        links_top,
+        right_width,
+        &"─".repeat(right_width as usize),
+        &dim,
+    )?;
+    let half = right_width / 2;
+    let backlinks: Vec<String> = app
+        .backlinks()
+        .iter()
+        .map(|n| format!(" ← {}  {}", n.zettel.id, n.zettel.title))
+        .collect();
+    let outgoing: Vec<String> = app
+        .outgoing()
+        .iter()
+        .map(|(id, note)| match note {
+            Some(note) => format!(" → {}  {}", id, note.zettel.title),
+            None => format!(" → {}  (not listed)", id),
+        })
+        .collect();
+    for (x, panel_width, label, lines) in [
+        (right, half, "Backlinks", &backlinks),
+        (right + half, right_width - half, "Outgoing", &outgoing),
+    ] {
+        let heading = format!(" {} ({})", label, lines.len());
+        write_line(w, x, links_top + 1, panel_width, &heading, &title)?;
+        for row in 0..links_height - 1 {
+            let line = lines.get(row as usize).map(String::as_str).unwrap_or("");
+            write_line(w, x, links_top + 2 + row, panel_width, line, &normal)?;
+        }
+    }
+
+    // ────────────── ステータス ──────────────
+    let status = match (&app.mode, &app.message) {
+        (TuiMode::Tag(input), _) => format!(" Add tags (comma separated): {}▏", input),
+        (TuiMode::ConfirmArchive, _) => " Archive this note? [y/N]".to_string(),
+        (TuiMode::Filter, _) => " Type to filter  ↑↓ move  Enter/Esc done".to_string(),
+        (_, Some(message)) => format!(" {}", message),
+        _ => " j/k move  / filter  e edit  a archive  t tag  y copy link  J/K scroll  q quit"
+            .to_string(),
+    };
+    write_line(w, 0, height - 1, width, &status, &dim)?;
+
+    w.flush()?;
+    Ok(())
+}
+
+// 幅に収まるよう切り詰め、残りは空白で埋める
+fn write_line<W: Write>(
+    w: &mut W,
+    x: u16,
+    y: u16,
+    width: u16,
+    text: &str,
+    style: &CompoundStyle,
+) -> Result<()> {
+    w.queue(MoveTo(x, y))?;
+    let mut cw = CropWriter::new(w, width as usize);
+    cw.queue_str(style, text)?;
+    cw.fill_with_space(style)?;
+    Ok(())
+}
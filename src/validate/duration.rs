@@ -0,0 +1,40 @@
+use chrono::Duration;
+
+// `30d` / `2w` / `12h` のような期間指定（`--older-than`）
+pub fn parse_age(s: &str) -> Result<Duration, String> {
+    let s = s.trim();
+    let (number, unit) = s.split_at(s.find(|c: char| !c.is_ascii_digit()).unwrap_or(s.len()));
+    let n: i64 = number
+        .parse()
+        .map_err(|_| format!("invalid duration \"{}\" (expected e.g. 30d, 2w, 12h)", s))?;
+
+    let age = match unit {
+        "h" => Duration::try_hours(n),
+        "d" | "" => Duration::try_days(n),
+        "w" => Duration::try_weeks(n),
+        _ => {
+            return Err(format!(
+                "invalid duration unit \"{}\" (expected h, d or w)",
+                unit
+            ));
+        }
+    };
+    age.ok_or_else(|| format!("duration \"{}\" is too large", s))
+}
+
+// Test
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_age() {
+        assert_eq!(parse_age("30d").unwrap(), Duration::days(30));
+        assert_eq!(parse_age("2w").unwrap(), Duration::weeks(2));
+        assert_eq!(parse_age("12h").unwrap(), Duration::hours(12));
+        assert_eq!(parse_age("7").unwrap(), Duration::days(7));
+        assert!(parse_age("d").is_err());
+        assert!(parse_age("3m").is_err());
+        assert!(parse_age("99999999999999d").is_err());
+    }
+}
@@ -1,3 +1,7 @@
+pub mod duration;
+pub mod rules;
 pub mod tag;
 
+pub use duration::*;
+pub use rules::*;
 pub use tag::*;
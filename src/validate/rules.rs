@@ -0,0 +1,144 @@
+use regex::Regex;
+
+use crate::{NoteType, TypeRules, ZettariumError, extract_note_links, replace_note_links};
+
+// ルールを満たしていない項目を返す（空なら満たしている）
+pub fn check_type_rules(rules: &TypeRules, tags: &[String], body: &str) -> Vec<String> {
+    let mut violations = vec![];
+
+    if tags.len() < rules.min_tags {
+        violations.push(format!(
+            "needs at least {} tag(s), has {}",
+            rules.min_tags,
+            tags.len()
+        ));
+    }
+
+    let links = extract_note_links(body).len();
+    if links < rules.min_links {
+        violations.push(format!(
+            "needs at least {} link(s) to other notes, has {}",
+            rules.min_links, links
+        ));
+    }
+
+    if rules.links_only {
+        let list_item = Regex::new(r"^(?:[-*+]|\d+\.)(?:\s+|$)").unwrap();
+        let lines: Vec<&str> = body
+            .lines()
+            .map(str::trim)
+            .filter(|line| !line.is_empty() && !line.starts_with('#'))
+            // 箇条書きの記号とリンクを除いて何も残らない行だけを許す
+            .filter(|line| {
+                let rest = list_item.replace(line, "");
+                let rest = rest.trim();
+                if rest.is_empty() {
+                    return false;
+                }
+                let without_links = replace_note_links(rest, |_, _| String::new());
+                !without_links.trim().is_empty()
+            })
+            .collect();
+        if !lines.is_empty() {
+            violations.push(format!(
+                "may only contain links to other notes, found \"{}\"",
+                lines[0]
+            ));
+        }
+    }
+
+    violations
+}
+
+// 種類を変える前にルールを確かめる（force なら警告だけ出して続ける）
+pub fn ensure_type_rules(
+    rules: &TypeRules,
+    id: &str,
+    type_: &NoteType,
+    tags: &[String],
+    body: &str,
+    force: bool,
+) -> Result<(), ZettariumError> {
+    let violations = check_type_rules(rules, tags, body);
+    if violations.is_empty() {
+        return Ok(());
+    }
+    if !force {
+        return Err(ZettariumError::RuleViolation {
+            id: id.to_string(),
+            type_: type_.to_string(),
+            violations,
+        });
+    }
+    for violation in &violations {
+        eprintln!("Warning: {}", violation);
+    }
+    Ok(())
+}
+
+// Test
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_check_type_rules() {
+        let permanent = TypeRules::builtin(&NoteType::Permanent);
+        assert_eq!(
+            check_type_rules(&permanent, &[], "## Idea\n\nJust a thought.\n"),
+            vec![
+                "needs at least 1 tag(s), has 0",
+                "needs at least 1 link(s) to other notes, has 0"
+            ]
+        );
+        assert!(
+            check_type_rules(
+                &permanent,
+                &["rust".into()],
+                "## Idea\n\nBuilds on [Ownership](./20250601T120000.md).\n"
+            )
+            .is_empty()
+        );
+
+        let index = TypeRules::builtin(&NoteType::Index);
+        assert!(
+            check_type_rules(
+                &index,
+                &[],
+                "## Rust\n\n- [Ownership](./20250601T120000.md)\n- \n1. [Borrowing](./20250601T120001.md)\n"
+            )
+            .is_empty()
+        );
+        assert_eq!(
+            check_type_rules(
+                &index,
+                &[],
+                "## Rust\n\n- [Ownership](./20250601T120000.md) is key\n"
+            ),
+            vec![
+                "may only contain links to other notes, found \"- [Ownership](./20250601T120000.md) is key\""
+            ]
+        );
+
+        // どの書式のリンクも数える
+        let two_links = TypeRules {
+            min_links: 2,
+            ..TypeRules::builtin(&NoteType::Permanent)
+        };
+        for body in [
+            "See [[20250601T120000|Ownership]] and [[20250601T120001]].\n",
+            "See [[file:20250601T120000.md][Ownership]] and 20250601T120001.\n",
+            "See [Ownership](./20250601T120000.md) and [[20250601T120001|Borrowing]].\n",
+        ] {
+            assert!(check_type_rules(&two_links, &["rust".into()], body).is_empty());
+        }
+        assert!(
+            check_type_rules(
+                &index,
+                &[],
+                "- [[20250601T120000|Ownership]]\n- [[file:20250601T120001.md][Borrowing]]\n- 20250601T120002\n"
+            )
+            .is_empty()
+        );
+    }
+}
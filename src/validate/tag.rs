@@ -7,7 +7,7 @@ pub fn dedup_and_warn(tags: Vec<String>) -> Vec<String> {
     for tag in tags {
         let tag_lower = tag.to_lowercase();
         if seen.contains(&tag_lower) {
-            println!("Waring: Duplicate tag \"{tag}\" detected and skipped.");
+            eprintln!("Warning: Duplicate tag \"{tag}\" detected and skipped.");
         } else {
             seen.insert(tag_lower.clone());
             unique.push(tag);
@@ -0,0 +1,233 @@
+use std::fmt;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use which::which;
+
+use crate::{AppConfig, ZettariumError, Zettel, assets_dir};
+
+// 自動コミットのきっかけになった操作（コミットメッセージの接頭辞）
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GitAction {
+    New,
+    Attach,
+    Edit,
+    Archive,
+    Remove,
+    Restore,
+    Purge,
+    Revert,
+}
+
+impl fmt::Display for GitAction {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let s = match self {
+            GitAction::New => "new",
+            GitAction::Attach => "attach",
+            GitAction::Edit => "edit",
+            GitAction::Archive => "archive",
+            GitAction::Remove => "remove",
+            GitAction::Restore => "restore",
+            GitAction::Purge => "purge",
+            GitAction::Revert => "revert",
+        };
+        write!(f, "{}", s)
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct GitCommit {
+    pub hash: String,
+    pub date: String,
+    pub subject: String,
+}
+
+// `edit: 20250608T120000 "Title"`
+pub fn commit_message(action: GitAction, zettel: &Zettel) -> String {
+    format!("{}: {} \"{}\"", action, zettel.id, zettel.title)
+}
+
+// ノートとその添付ファイルが置かれうる場所（有効 / アーカイブ / ゴミ箱）
+pub fn note_paths(config: &AppConfig, id: &str) -> Vec<PathBuf> {
+    let trash_dir = config.paths.trash_dir();
+    [
+        config.paths.zettel_dir.as_str(),
+        config.paths.archive_dir.as_str(),
+        trash_dir.as_str(),
+    ]
+    .into_iter()
+    .flat_map(|dir| {
+        [
+            PathBuf::from(format!("{}/{}.md", dir, id)),
+            assets_dir(dir, id),
+        ]
+    })
+    .collect()
+}
+
+// 設定で有効な場合だけノートの変更をコミットする
+// コミットに失敗してもノートの操作自体は取り消さない
+pub fn auto_commit(config: &AppConfig, action: GitAction, zettel: &Zettel) {
+    if let Some(message) = commit_note(config, action, zettel) {
+        eprintln!("{}", message);
+    }
+}
+
+// auto_commitの結果を表示せずに返す（TUIのステータス行用）
+pub fn commit_note(config: &AppConfig, action: GitAction, zettel: &Zettel) -> Option<String> {
+    if !config.git.auto_commit {
+        return None;
+    }
+
+    let message = commit_message(action, zettel);
+    let paths = note_paths(config, &zettel.id);
+    let result = GitRepo::discover(Path::new(&config.paths.zettel_dir)).and_then(|repo| {
+        repo.map(|repo| repo.commit_paths(&paths, &message))
+            .transpose()
+    });
+
+    match result {
+        Ok(Some(true)) => Some(format!("Committed: {}", message)),
+        Ok(Some(false)) => None,
+        Ok(None) => Some(format!(
+            "Warning: {} is not a git repository. Skipped auto-commit.",
+            config.paths.zettel_dir
+        )),
+        Err(e) => Some(format!("Warning: Auto-commit failed: {}", e)),
+    }
+}
+
+pub struct GitRepo {
+    root: PathBuf,
+}
+
+impl GitRepo {
+    // dirがgitの作業ツリー内にあればそのリポジトリを返す
+    pub fn discover(dir: &Path) -> Result<Option<GitRepo>, ZettariumError> {
+        if which("git").is_err() {
+            return Err(ZettariumError::Git("git not found in PATH".into()));
+        }
+        let output = Command::new("git")
+            .arg("-C")
+            .arg(dir)
+            .args(["rev-parse", "--show-toplevel"])
+            .output()
+            .map_err(|e| ZettariumError::Git(format!("could not run git: {}", e)))?;
+        if !output.status.success() {
+            return Ok(None);
+        }
+
+        let root = String::from_utf8_lossy(&output.stdout).trim().to_string();
+        Ok(Some(GitRepo { root: root.into() }))
+    }
+
+    pub fn root(&self) -> &Path {
+        &self.root
+    }
+
+    // 作業ツリー内のパスをリポジトリからの相対パスにする（外側ならNone）
+    pub fn relative_path(&self, path: &Path) -> Option<PathBuf> {
+        // 削除済みのファイルもあるので親ディレクトリで正規化する
+        let parent = path.parent()?.canonicalize().ok()?;
+        let root = self.root.canonicalize().ok()?;
+        let relative = parent.strip_prefix(&root).ok()?;
+        Some(relative.join(path.file_name()?))
+    }
+
+    // 指定したパスの変更だけをコミットする（他にステージ済みの変更があっても含めない）
+    // コミットするものがなければfalse
+    pub fn commit_paths(&self, paths: &[PathBuf], message: &str) -> Result<bool, ZettariumError> {
+        let mut targets = vec![];
+        for path in paths {
+            let Some(relative) = self.relative_path(path) else {
+                continue;
+            };
+            // 存在しないファイルは、追跡中のもの（削除・移動元）だけを対象にする
+            if self.root.join(&relative).exists() || self.is_tracked(&relative)? {
+                targets.push(relative);
+            }
+        }
+        if targets.is_empty() {
+            return Ok(false);
+        }
+
+        self.run(["add", "-A", "--"], &targets)?;
+        if self.status(["diff", "--cached", "--quiet", "--"], &targets)? {
+            return Ok(false);
+        }
+        self.run(["commit", "--quiet", "-m", message, "--"], &targets)?;
+        Ok(true)
+    }
+
+    // パスに関わるコミットを新しい順に返す
+    pub fn log(&self, paths: &[PathBuf]) -> Result<Vec<GitCommit>, ZettariumError> {
+        let targets: Vec<PathBuf> = paths.iter().filter_map(|p| self.relative_path(p)).collect();
+        if targets.is_empty() {
+            return Ok(vec![]);
+        }
+
+        let output = self.run(
+            [
+                "log",
+                "--format=%h%x09%ad%x09%s",
+                "--date=format:%Y/%m/%d %H:%M:%S",
+                "--",
+            ],
+            &targets,
+        )?;
+        Ok(output
+            .lines()
+            .filter_map(|line| {
+                let mut fields = line.splitn(3, '\t');
+                Some(GitCommit {
+                    hash: fields.next()?.to_string(),
+                    date: fields.next()?.to_string(),
+                    subject: fields.next().unwrap_or_default().to_string(),
+                })
+            })
+            .collect())
+    }
+
+    fn is_tracked(&self, path: &Path) -> Result<bool, ZettariumError> {
+        self.status(["ls-files", "--error-unmatch", "--"], &[path.to_path_buf()])
+    }
+
+    fn command<'a>(&self, args: impl IntoIterator<Item = &'a str>, paths: &[PathBuf]) -> Command {
+        let mut command = Command::new("git");
+        command
+            .arg("-C")
+            .arg(&self.root)
+            .args(args)
+            .args(paths.iter().map(|p| p.as_os_str()));
+        command
+    }
+
+    fn run<'a>(
+        &self,
+        args: impl IntoIterator<Item = &'a str>,
+        paths: &[PathBuf],
+    ) -> Result<String, ZettariumError> {
+        let output = self
+            .command(args, paths)
+            .output()
+            .map_err(|e| ZettariumError::Git(format!("could not run git: {}", e)))?;
+        if !output.status.success() {
+            return Err(ZettariumError::Git(
+                String::from_utf8_lossy(&output.stderr).trim().to_string(),
+            ));
+        }
+        Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+    }
+
+    // 終了コードだけを見る（0ならtrue）
+    fn status<'a>(
+        &self,
+        args: impl IntoIterator<Item = &'a str>,
+        paths: &[PathBuf],
+    ) -> Result<bool, ZettariumError> {
+        let output = self
+            .command(args, paths)
+            .output()
+            .map_err(|e| ZettariumError::Git(format!("could not run git: {}", e)))?;
+        Ok(output.status.success())
+    }
+}
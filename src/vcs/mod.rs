@@ -0,0 +1,3 @@
+pub mod git;
+
+pub use git::*;